@@ -1,32 +1,85 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+#[derive(Debug, PartialEq)]
+pub enum BoardError {
+    Empty,
+    RaggedRow { row: usize, expected: usize, actual: usize },
+    InvalidValue { row: usize, value: String },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::Empty => write!(f, "board has no values"),
+            BoardError::RaggedRow { row, expected, actual } =>
+                write!(f, "row {} has {} values (expected: {})", row, actual, expected),
+            BoardError::InvalidValue { row, value } =>
+                write!(f, "row {} contains invalid value '{}'", row, value),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Board {
     pub board_index: usize,
-    pub board_size: usize,
+    pub num_rows: usize,
+    pub num_columns: usize,
     pub values: Vec<u32>,
     matches: Vec<usize>,
     pub has_won: bool,
 }
 
 impl Board {
-    pub fn new(board_index: usize, board_size: usize, values: Vec<u32>) -> Self {
-        if values.len() != board_size * board_size {
-            panic!("Invalid number of values for board {} (expected: {}, actual: {})", board_index, board_size * board_size, values.len());
+    pub fn new(board_index: usize, num_rows: usize, num_columns: usize, values: Vec<u32>) -> Self {
+        if num_rows == 0 || num_columns == 0 || values.len() != num_rows * num_columns {
+            panic!("Invalid number of values for board {} (expected: {}, actual: {})", board_index, num_rows * num_columns, values.len());
         }
-        
 
         Board {
             board_index,
-            board_size,
+            num_rows,
+            num_columns,
             values,
             matches: vec![],
             has_won: false,
         }
     }
 
+    pub fn parse<T: AsRef<str>>(board_index: usize, lines: &[T]) -> Result<Self, BoardError> {
+        let mut num_columns = None;
+        let mut values = vec![];
+
+        for (row, line) in lines.iter().enumerate() {
+            let row_values = line.as_ref().split_whitespace()
+                .map(|value| value.parse::<u32>().map_err(|_| BoardError::InvalidValue { row, value: value.to_string() }))
+                .collect::<Result<Vec<u32>, _>>()?;
+
+            match num_columns {
+                None => num_columns = Some(row_values.len()),
+                Some(expected) if expected != row_values.len() =>
+                    return Err(BoardError::RaggedRow { row, expected, actual: row_values.len() }),
+                _ => {}
+            }
+
+            values.extend(row_values);
+        }
+
+        match num_columns {
+            Some(num_columns) if num_columns > 0 => Ok(Board::new(board_index, lines.len(), num_columns, values)),
+            _ => Err(BoardError::Empty),
+        }
+    }
+
+    // Copy of the board without any marked numbers
+    pub fn cleared(&self) -> Self {
+        Board::new(self.board_index, self.num_rows, self.num_columns, self.values.clone())
+    }
+
     pub fn update(&mut self, number: u32) {
         if let Some(position) = self.values.iter().position(|value| *value == number) {
             self.matches.push(position);
-            
+
         } else {
             return;
         }
@@ -35,42 +88,48 @@ impl Board {
             return;
         }
 
-        if self.matches.len() < self.board_size {
+        if self.matches.len() < self.num_rows.min(self.num_columns) {
             return;
         }
 
-        
+
         let (rows, columns): (Vec<_>, Vec<_>) = self.matches.clone()
             .into_iter()
             .map(|position| (
-                position / self.board_size,
-                position % self.board_size
+                position / self.num_columns,
+                position % self.num_columns
             ))
             .unzip();
-        
 
-        for i in 0..self.board_size {
-            let is_row_full = rows.iter().filter(|row_index| **row_index == i).count() >= self.board_size;
 
-            if is_row_full {
-                self.has_won = true;
-                break;
-            }
+        let is_row_full = (0..self.num_rows)
+            .any(|i| rows.iter().filter(|row_index| **row_index == i).count() >= self.num_columns);
 
-            let is_column_full = columns.iter().filter(|column_index| **column_index == i).count() >= self.board_size;
+        let is_column_full = (0..self.num_columns)
+            .any(|i| columns.iter().filter(|column_index| **column_index == i).count() >= self.num_rows);
 
-            if is_column_full {
-                self.has_won = true;
-                break;
-            }
-        }
+        self.has_won = is_row_full || is_column_full;
     }
 
     pub fn score(&self) -> u32 {
         self.values.iter()
             .enumerate()
-            .filter(|(i, value)| !self.matches.contains(i))
-            .map(|(i, value)| value)
+            .filter(|(i, _)| !self.matches.contains(i))
+            .map(|(_, value)| value)
             .sum()
     }
-}
\ No newline at end of file
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = self.values
+            .chunks(self.num_columns)
+            .map(|row| row.iter().map(|value| format!("{:>2}", value)).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<String>>();
+
+        write!(
+            f, "{}",
+            lines.join("\n")
+        )
+    }
+}
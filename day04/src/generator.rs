@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use crate::board::Board;
+use crate::random::Random;
+
+pub struct CardGenerator {
+    num_rows: usize,
+    num_columns: usize,
+    numbers: RangeInclusive<u32>,
+}
+
+impl CardGenerator {
+    pub fn new(num_rows: usize, num_columns: usize, numbers: RangeInclusive<u32>) -> Self {
+        let num_numbers = if numbers.is_empty() { 0 } else { *numbers.end() as u64 - *numbers.start() as u64 + 1 };
+
+        if num_rows == 0 || num_columns == 0 || num_numbers < (num_rows * num_columns) as u64 {
+            panic!("Cannot fill a {}x{} card with {} distinct numbers", num_rows, num_columns, num_numbers);
+        }
+
+        CardGenerator {
+            num_rows,
+            num_columns,
+            numbers,
+        }
+    }
+
+    pub fn generate(&self, board_index: usize, rng: &mut Random) -> Board {
+        let num_values = (self.num_rows * self.num_columns) as u64;
+        let (start, end) = (*self.numbers.start() as u64, *self.numbers.end() as u64);
+        let mut numbers: Vec<u32> = Vec::with_capacity(num_values as usize);
+        let mut chosen: HashSet<u32> = HashSet::new();
+
+        // Floyd's sampling: distinct values without building the range, shuffled into card order
+        for j in end + 1 - num_values..=end {
+            let candidate = (start + rng.next_below(j - start + 1)) as u32;
+            let value = if chosen.contains(&candidate) { j as u32 } else { candidate };
+
+            chosen.insert(value);
+            numbers.push(value);
+        }
+
+        rng.shuffle(&mut numbers);
+
+        Board::new(board_index, self.num_rows, self.num_columns, numbers)
+    }
+}
+
+// Boards that win on the same number share the credit for that trial, so the first and last
+// probabilities each sum to 1 as long as every board can complete with the called numbers.
+#[derive(Debug)]
+pub struct WinProbability {
+    pub board_index: usize,
+    pub first: f64,
+    pub last: f64,
+}
+
+pub fn estimate_win_probabilities(boards: &[Board], numbers: &[u32], num_trials: usize, rng: &mut Random) -> Vec<WinProbability> {
+    let mut first_wins = vec![0f64; boards.len()];
+    let mut last_wins = vec![0f64; boards.len()];
+    let mut call_order = numbers.to_vec();
+
+    for _ in 0..num_trials {
+        rng.shuffle(&mut call_order);
+
+        let rounds = winning_rounds(boards, &call_order);
+
+        if let Some(first) = rounds.iter().flatten().min() {
+            share_credit(&mut first_wins, &rounds, *first);
+        }
+
+        // without a round in which the last board completes, nobody gets the last-win credit
+        if rounds.iter().all(Option::is_some) {
+            if let Some(last) = rounds.iter().flatten().max() {
+                share_credit(&mut last_wins, &rounds, *last);
+            }
+        }
+    }
+
+    boards.iter()
+        .enumerate()
+        .map(|(i, board)| WinProbability {
+            board_index: board.board_index,
+            first: first_wins[i] / num_trials.max(1) as f64,
+            last: last_wins[i] / num_trials.max(1) as f64,
+        })
+        .collect()
+}
+
+// Position in the call order at which each board completes a row or column, if it does
+fn winning_rounds(boards: &[Board], call_order: &[u32]) -> Vec<Option<usize>> {
+    boards.iter()
+        .map(|board| {
+            let mut board = board.cleared();
+
+            call_order.iter().position(|number| {
+                board.update(*number);
+                board.has_won
+            })
+        })
+        .collect()
+}
+
+fn share_credit(wins: &mut [f64], rounds: &[Option<usize>], round: usize) {
+    let winners = rounds.iter().filter(|other| **other == Some(round)).count();
+
+    for (wins, other) in wins.iter_mut().zip(rounds) {
+        if *other == Some(round) {
+            *wins += 1.0 / winners as f64;
+        }
+    }
+}
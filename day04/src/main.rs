@@ -1,33 +1,100 @@
 mod board;
+mod generator;
+mod random;
 
-use board::Board;
+use board::{Board, BoardError};
+use generator::{estimate_win_probabilities, CardGenerator};
+use random::Random;
 
 fn main() {
-    let board_size = 5;
-
     let input = include_str!("../resources/input");
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.get(1).map(String::as_str) {
+        Some("generate") => {
+            let num_cards: usize = args.get(2).map_or(1, |v| v.parse().unwrap());
+            let num_rows: usize = args.get(3).map_or(5, |v| v.parse().unwrap());
+            let num_columns: usize = args.get(4).map_or(num_rows, |v| v.parse().unwrap());
+            let max_number: u32 = args.get(5).map_or(99, |v| v.parse().unwrap());
+            let seed: u64 = args.get(6).map_or(0, |v| v.parse().unwrap());
+
+            let generator = CardGenerator::new(num_rows, num_columns, 0..=max_number);
+            let mut rng = Random::new(seed);
+
+            let cards = (0..num_cards)
+                .map(|i| generator.generate(i, &mut rng).to_string())
+                .collect::<Vec<_>>();
+
+            println!("{}", cards.join("\n\n"));
+            return;
+        }
+        Some("simulate") => {
+            let num_trials: usize = args.get(2).map_or(10000, |v| v.parse().unwrap());
+            let seed: u64 = args.get(3).map_or(0, |v| v.parse().unwrap());
+
+            let (bingo_numbers, boards) = load_valid_data(input);
+            let mut rng = Random::new(seed);
 
+            for probability in estimate_win_probabilities(&boards, &bingo_numbers, num_trials, &mut rng) {
+                println!("Board {}: first {:.4}, last {:.4}", probability.board_index, probability.first, probability.last);
+            }
+            return;
+        }
+        _ => {}
+    }
 
-    let (bingo_numbers, mut boards) = load_data(input, board_size);
+    let (bingo_numbers, mut boards) = load_valid_data(input);
     let (_, score ) = find_winner(&bingo_numbers, &mut boards).unwrap();
     println!("[1/2] Result: {}", score);
 
-    let (bingo_numbers, boards) = load_data(input, board_size);
+    let (bingo_numbers, boards) = load_valid_data(input);
     let (_, score ) = find_last_winner(&bingo_numbers, boards).unwrap();
     println!("[2/2] Result: {}", score);
 }
 
-fn load_data(input: &str, board_size: usize) -> (Vec<u32>, Vec<Board>) {
-    let mut lines = input.trim().split('\n').collect::<Vec<&str>>().into_iter();
-    let bingo_numbers: Vec<u32> = lines.next().unwrap().split(',').map(|c| c.parse::<u32>().unwrap()).collect();
+fn load_valid_data(input: &str) -> (Vec<u32>, Vec<Board>) {
+    let (bingo_numbers, boards) = load_data(input);
+
+    let boards = boards.into_iter()
+        .enumerate()
+        .filter_map(|(i, board)| match board {
+            Ok(board) => Some(board),
+            Err(e) => {
+                eprintln!("Skipping board {}: {}", i, e);
+                None
+            }
+        })
+        .collect();
+
+    (bingo_numbers, boards)
+}
+
+fn load_data(input: &str) -> (Vec<u32>, Vec<Result<Board, BoardError>>) {
+    let mut lines = input.trim().split('\n').map(|line| line.trim());
+    let bingo_numbers: Vec<u32> = str_to_numbers(lines.next().unwrap().replace(',', " "));
+
+    let mut groups: Vec<Vec<&str>> = vec![];
+    let mut current: Vec<&str> = vec![];
+
+    for line in lines {
+        if line.is_empty() {
+            if !current.is_empty() {
+                groups.push(current);
+                current = vec![];
+            }
+        } else {
+            current.push(line);
+        }
+    }
+
+    if !current.is_empty() {
+        groups.push(current);
+    }
 
-    let boards: Vec<Board> = 
-        lines.as_slice()
-            .chunks(board_size + 1)
-            .map(|board_lines| board_lines.join(" ").trim().into())
-            .map(|line: String| str_to_numbers(&line))
+    let boards: Vec<Result<Board, BoardError>> =
+        groups.iter()
             .enumerate()
-            .map(|(i, values)| Board::new(i, board_size, values))
+            .map(|(i, board_lines)| Board::parse(i, board_lines))
             .collect();
 
 
@@ -38,7 +105,7 @@ fn str_to_numbers<T: AsRef<str>>(input: T) -> Vec<u32> {
     input.as_ref().split_whitespace().map(|v|v.parse::<u32>()).collect::<Result<Vec<u32>, _>>().unwrap()
 }
 
-fn find_winner(bingo_numbers: &Vec<u32>, boards: &mut Vec<Board>) -> Option<(usize, u32)> {
+fn find_winner(bingo_numbers: &[u32], boards: &mut [Board]) -> Option<(usize, u32)> {
     for number in bingo_numbers {
         for board in &mut boards.iter_mut() {
             board.update(*number);
@@ -54,7 +121,7 @@ fn find_winner(bingo_numbers: &Vec<u32>, boards: &mut Vec<Board>) -> Option<(usi
     None
 }
 
-fn find_last_winner(bingo_numbers: &Vec<u32>, boards: Vec<Board>) -> Option<(usize, u32)> {
+fn find_last_winner(bingo_numbers: &[u32], boards: Vec<Board>) -> Option<(usize, u32)> {
 
     let mut boards = boards;
     
@@ -71,7 +138,7 @@ fn find_last_winner(bingo_numbers: &Vec<u32>, boards: Vec<Board>) -> Option<(usi
             }
         }
 
-        boards = boards.into_iter().filter(|board| !board.has_won).collect();
+        boards.retain(|board| !board.has_won);
     }
 
     None
@@ -106,9 +173,7 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let board_size = 5;
-
-        let (bingo_numbers, mut boards) = load_data(TEST_DATA, board_size);
+        let (bingo_numbers, mut boards) = load_valid_data(TEST_DATA);
         let (board_index, score ) = find_winner(&bingo_numbers, &mut boards).unwrap();
 
         assert_eq!(2, board_index);
@@ -117,12 +182,98 @@ mod tests {
 
     #[test]
     fn test_part2() {
-        let board_size = 5;
-
-        let (bingo_numbers, boards) = load_data(TEST_DATA, board_size);
+        let (bingo_numbers, boards) = load_valid_data(TEST_DATA);
         let (board_index, score ) = find_last_winner(&bingo_numbers, boards).unwrap();
 
         assert_eq!(1, board_index);
         assert_eq!(1924, score);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_variable_board_sizes() {
+        let input = "
+            1,2,3,4,5,6,7
+
+            1 2 3
+            4 5 6
+
+
+            7 8
+            9 1
+            2 3
+
+            1 2 x
+            3 4 5
+
+            1 2 3
+            4 5
+        ";
+
+        let (bingo_numbers, boards) = load_data(input);
+        assert_eq!(4, boards.len());
+        assert_eq!(Some(&BoardError::InvalidValue { row: 0, value: "x".to_string() }), boards[2].as_ref().err());
+        assert_eq!(Some(&BoardError::RaggedRow { row: 1, expected: 3, actual: 2 }), boards[3].as_ref().err());
+
+        let (_, mut boards) = load_valid_data(input);
+        assert_eq!(2, boards.len());
+        assert_eq!((3, 2), (boards[1].num_rows, boards[1].num_columns));
+
+        let (board_index, score) = find_winner(&bingo_numbers, &mut boards).unwrap();
+        assert_eq!(0, board_index);
+        assert_eq!(15 * 3, score);
+    }
+
+    #[test]
+    fn test_generator_and_win_probabilities() {
+        let generator = CardGenerator::new(3, 4, 1..=20);
+        let mut rng = Random::new(42);
+
+        let boards = (0..4).map(|i| generator.generate(i, &mut rng)).collect::<Vec<_>>();
+
+        for board in &boards {
+            let mut values = board.values.clone();
+            values.sort_unstable();
+            values.dedup();
+
+            assert_eq!(12, values.len());
+            assert!(values.iter().all(|value| (1..=20).contains(value)));
+        }
+
+        // ranges are sampled without being built, and a range of exactly the card size is used up
+        let card = CardGenerator::new(5, 5, 0..=u32::MAX).generate(0, &mut rng);
+        assert_eq!(25, card.values.iter().collect::<std::collections::HashSet<_>>().len());
+
+        let mut values = CardGenerator::new(3, 4, 1..=12).generate(0, &mut rng).values;
+        values.sort_unstable();
+        assert_eq!((1..=12).collect::<Vec<u32>>(), values);
+
+        let numbers = (1..=20).collect::<Vec<u32>>();
+        let probabilities = estimate_win_probabilities(&boards, &numbers, 500, &mut rng);
+
+        let total_first: f64 = probabilities.iter().map(|p| p.first).sum();
+        let total_last: f64 = probabilities.iter().map(|p| p.last).sum();
+        assert!((total_first - 1.0).abs() < 1e-9);
+        assert!((total_last - 1.0).abs() < 1e-9);
+
+        // a single cell against a row of two, which completes a column with either of its numbers
+        let boards = vec![Board::new(0, 1, 1, vec![1]), Board::new(1, 1, 2, vec![2, 3])];
+        let probabilities = estimate_win_probabilities(&boards, &[1, 2, 3], 4000, &mut rng);
+
+        assert!((probabilities[0].first - 1.0 / 3.0).abs() < 0.05);
+        assert!((probabilities[1].first - 2.0 / 3.0).abs() < 0.05);
+        assert!((probabilities[0].last - 2.0 / 3.0).abs() < 0.05);
+        assert!((probabilities[1].last - 1.0 / 3.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_tied_wins() {
+        let boards = vec![Board::new(0, 2, 2, vec![1, 2, 3, 4]), Board::new(1, 2, 2, vec![4, 3, 2, 1])];
+        let mut rng = Random::new(7);
+
+        // both boards complete on the same number in every order, so they split every trial
+        for probability in estimate_win_probabilities(&boards, &[1, 2, 3, 4], 100, &mut rng) {
+            assert!((probability.first - 0.5).abs() < 1e-9);
+            assert!((probability.last - 0.5).abs() < 1e-9);
+        }
+    }
+}
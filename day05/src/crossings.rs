@@ -0,0 +1,220 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::status::{Status, Tree};
+use crate::sweep::{Carrier, Run};
+
+// Exact sweep position (x / d, y / d) with d > 0, ordered by x, then y
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    x: i128,
+    y: i128,
+    d: i128,
+}
+
+impl Point {
+    fn lattice((x, y): (i64, i64)) -> Self {
+        Point { x: x as i128, y: y as i128, d: 1 }
+    }
+
+    fn as_lattice(&self) -> Option<(i64, i64)> {
+        if self.x % self.d != 0 || self.y % self.d != 0 {
+            return None;
+        }
+
+        Some(((self.x / self.d) as i64, (self.y / self.d) as i64))
+    }
+}
+
+impl Ord for Point {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_products(self.x, other.d, other.x, self.d)
+            .then_with(|| compare_products(self.y, other.d, other.y, self.d))
+    }
+}
+
+impl PartialOrd for Point {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Point {}
+
+// A run as a geometric segment, start before end in sweep order
+struct Segment {
+    start: (i64, i64),
+    end: (i64, i64),
+}
+
+impl Segment {
+    fn direction(&self) -> (i128, i128) {
+        ((self.end.0 - self.start.0) as i128, (self.end.1 - self.start.1) as i128)
+    }
+
+    fn is_vertical(&self) -> bool {
+        self.start.0 == self.end.0
+    }
+
+    // Less if the segment passes below the point. Only asked for points within the segment's
+    // x range, a vertical segment is in the status only while the sweep runs along it.
+    fn side_of(&self, point: &Point) -> Ordering {
+        if self.is_vertical() {
+            return Ordering::Equal;
+        }
+
+        let (dx, dy) = self.direction();
+        let (x, y) = (point.x - self.start.0 as i128 * point.d, point.y - self.start.1 as i128 * point.d);
+
+        compare_products(dy, x, dx, y)
+    }
+
+    // Order directly behind a common point: by slope, vertical segments last
+    fn compare_slope(&self, other: &Segment) -> Ordering {
+        let ((dx1, dy1), (dx2, dy2)) = (self.direction(), other.direction());
+
+        match (self.is_vertical(), other.is_vertical()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => compare_products(dy1, dx2, dy2, dx1),
+        }
+    }
+
+    fn intersection(&self, other: &Segment) -> Option<Point> {
+        let ((rx, ry), (qx, qy)) = (self.direction(), other.direction());
+        let (wx, wy) = ((other.start.0 - self.start.0) as i128, (other.start.1 - self.start.1) as i128);
+
+        // parameters along both segments are t / denominator and u / denominator
+        let (mut denominator, mut t, mut u) = (rx * qy - ry * qx, wx * qy - wy * qx, wx * ry - wy * rx);
+
+        if denominator == 0 {
+            return None;
+        }
+
+        if denominator < 0 {
+            (denominator, t, u) = (-denominator, -t, -u);
+        }
+
+        if t < 0 || t > denominator || u < 0 || u > denominator {
+            return None;
+        }
+
+        Some(Point {
+            x: self.start.0 as i128 * denominator + rx * t,
+            y: self.start.1 as i128 * denominator + ry * t,
+            d: denominator,
+        })
+    }
+}
+
+// Bentley-Ottmann sweep over the runs, which never overlap each other: the status holds the runs
+// cut by the sweep line and only neighbours in it are tested, so the work is
+// O((runs + crossings) log runs). Crossings between more than two runs are handled in one event.
+pub fn find_crossings(carriers: &[Carrier], runs: &[Run]) -> HashMap<(i64, i64), Vec<(usize, usize)>> {
+    let segments = runs.iter()
+        .map(|run| {
+            let carrier = &carriers[run.carrier];
+            let (first, second) = (carrier.point_at(run.start), carrier.point_at(run.end));
+
+            Segment { start: first.min(second), end: first.max(second) }
+        })
+        .collect::<Vec<_>>();
+
+    // segments starting at each event point, other events only mark where the status changes
+    let mut events: BTreeMap<Point, Vec<usize>> = BTreeMap::new();
+
+    for (i, segment) in segments.iter().enumerate() {
+        events.entry(Point::lattice(segment.start)).or_default().push(i);
+        events.entry(Point::lattice(segment.end)).or_default();
+    }
+
+    let mut status = Status::default();
+    let mut root: Tree = None;
+    let mut crossings: HashMap<(i64, i64), Vec<(usize, usize)>> = HashMap::new();
+
+    while let Some((point, starting)) = events.pop_first() {
+        let (below, rest) = status.split(root, &mut |i| segments[i].side_of(&point) == Ordering::Less);
+        let (through, above) = status.split(rest, &mut |i| segments[i].side_of(&point) == Ordering::Equal);
+        let through = status.drain(through);
+
+        if starting.len() + through.len() > 1 {
+            if let Some(lattice) = point.as_lattice() {
+                let contributions = starting.iter().chain(&through).map(|i| (runs[*i].carrier, runs[*i].count));
+                crossings.insert(lattice, contributions.collect());
+            }
+        }
+
+        let mut continuing = through.into_iter()
+            .filter(|i| Point::lattice(segments[*i].end) != point)
+            .chain(starting.into_iter().filter(|i| segments[*i].start != segments[*i].end))
+            .collect::<Vec<_>>();
+        continuing.sort_by(|i, j| segments[*i].compare_slope(&segments[*j]));
+
+        let (lower, upper) = (status.last(below), status.first(above));
+
+        if continuing.is_empty() {
+            schedule(&mut events, &segments, &point, lower, upper);
+        } else {
+            schedule(&mut events, &segments, &point, lower, continuing.first().copied());
+            schedule(&mut events, &segments, &point, continuing.last().copied(), upper);
+        }
+
+        let middle = status.build(&continuing);
+        let lower_part = status.merge(below, middle);
+        root = status.merge(lower_part, above);
+    }
+
+    crossings
+}
+
+fn schedule(events: &mut BTreeMap<Point, Vec<usize>>, segments: &[Segment], point: &Point, first: Option<usize>, second: Option<usize>) {
+    if let (Some(first), Some(second)) = (first, second) {
+        if let Some(crossing) = segments[first].intersection(&segments[second]) {
+            if crossing > *point {
+                events.entry(crossing).or_default();
+            }
+        }
+    }
+}
+
+// Exact comparison of a * b with c * d, the products can need up to 254 bits
+fn compare_products(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    let (left, right) = (a.signum() * b.signum(), c.signum() * d.signum());
+
+    if left != right {
+        return left.cmp(&right);
+    }
+
+    let magnitudes = wide_mul(a.unsigned_abs(), b.unsigned_abs()).cmp(&wide_mul(c.unsigned_abs(), d.unsigned_abs()));
+
+    if left < 0 {
+        magnitudes.reverse()
+    } else {
+        magnitudes
+    }
+}
+
+// (high, low) halves of the 256 bit product
+fn wide_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+
+    let low = a_low * b_low;
+    let cross_1 = a_low * b_high;
+    let cross_2 = a_high * b_low;
+    let middle = (low >> 64) + (cross_1 & MASK) + (cross_2 & MASK);
+
+    (
+        a_high * b_high + (cross_1 >> 64) + (cross_2 >> 64) + (middle >> 64),
+        (low & MASK) | (middle << 64),
+    )
+}
@@ -0,0 +1,134 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rasterisation {
+    // only the points exactly on the segment, stepping by (dx, dy) / gcd(dx, dy)
+    Exact,
+    // every column (or row) along the major axis, rounded to the nearest lattice point
+    Bresenham,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Line {
+    pub fn is_horizontal(&self) -> bool {
+        self.y1 == self.y2
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.x1 == self.x2
+    }
+
+    pub fn is_axis_aligned(&self) -> bool {
+        self.is_horizontal() || self.is_vertical()
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32), rasterisation: Rasterisation) -> bool {
+        !self.points_within(rasterisation, (x, y), (x, y)).is_empty()
+    }
+
+    pub fn points(&self, rasterisation: Rasterisation) -> Vec<(i32, i32)> {
+        let steps = self.steps(rasterisation);
+
+        (0..=steps.last()).map(|i| steps.point(i)).collect()
+    }
+
+    // Only walks the part of the line inside the box, so long lines can be cropped cheaply
+    pub fn points_within(&self, rasterisation: Rasterisation, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32)> {
+        let steps = self.steps(rasterisation);
+        let (first, last) = steps.clip(min, max, (0, steps.last()));
+
+        (first..=last).map(|i| steps.point(i)).collect()
+    }
+
+    // Widened, the span between two i32 coordinates doesn't fit into an i32
+    pub fn steps(&self, rasterisation: Rasterisation) -> Steps {
+        let dx = self.x2 as i64 - self.x1 as i64;
+        let dy = self.y2 as i64 - self.y1 as i64;
+
+        let last = match rasterisation {
+            Rasterisation::Exact => gcd(dx.abs(), dy.abs()),
+            Rasterisation::Bresenham => dx.abs().max(dy.abs()),
+        };
+
+        Steps {
+            start: (self.x1 as i64, self.y1 as i64),
+            sign: (dx.signum(), dy.signum()),
+            delta: (dx.abs(), dy.abs()),
+            last,
+        }
+    }
+}
+
+// A rasterised line as a function of the step index i in 0..=last: along each axis the point
+// moves round(i * delta / last) away from the start, halves rounded up. For the exact points
+// last divides both deltas, for Bresenham it's the length of the major axis, which reproduces
+// the error stepping loop including its tie breaking.
+pub struct Steps {
+    start: (i64, i64),
+    sign: (i64, i64),
+    delta: (i64, i64),
+    last: i64,
+}
+
+impl Steps {
+    pub fn last(&self) -> i64 {
+        self.last
+    }
+
+    pub fn point(&self, i: i64) -> (i32, i32) {
+        let x = self.start.0 + self.sign.0 * self.offset(i, self.delta.0);
+        let y = self.start.1 + self.sign.1 * self.offset(i, self.delta.1);
+
+        (x as i32, y as i32)
+    }
+
+    // Narrows the step range to the points inside the box, empty once first > last
+    pub fn clip(&self, min: (i32, i32), max: (i32, i32), range: (i64, i64)) -> (i64, i64) {
+        let range = self.clip_axis(self.start.0, self.sign.0, self.delta.0, (min.0 as i64, max.0 as i64), range);
+        self.clip_axis(self.start.1, self.sign.1, self.delta.1, (min.1 as i64, max.1 as i64), range)
+    }
+
+    fn offset(&self, i: i64, delta: i64) -> i64 {
+        if self.last == 0 {
+            return 0;
+        }
+
+        // 2 * i * delta can take up to 67 bits
+        ((2 * i as i128 * delta as i128 + self.last as i128) / (2 * self.last as i128)) as i64
+    }
+
+    // The offsets never decrease along the line, so the steps within [min, max] form a range
+    fn clip_axis(&self, start: i64, sign: i64, delta: i64, (min, max): (i64, i64), (first, last): (i64, i64)) -> (i64, i64) {
+        if sign == 0 {
+            return if start < min || start > max { (1, 0) } else { (first, last) };
+        }
+
+        let (low, high) = if sign > 0 { (min - start, max - start) } else { (start - max, start - min) };
+        let (n, d) = (self.last as i128, delta as i128);
+
+        // offset(i) >= low and offset(i) <= high, solved for i
+        let lower = -((n - 2 * n * low as i128).div_euclid(2 * d));
+        let upper = (2 * n * (high as i128 + 1) - n - 1).div_euclid(2 * d);
+
+        let (first, last) = ((first as i128).max(lower), (last as i128).min(upper));
+
+        if first > last {
+            (1, 0)
+        } else {
+            (first as i64, last as i64)
+        }
+    }
+}
+
+pub fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
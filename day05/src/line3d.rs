@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::line::gcd;
+use crate::sweep::{count_covered, merge_intervals, Run};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line3d {
+    pub x1: i32,
+    pub y1: i32,
+    pub z1: i32,
+    pub x2: i32,
+    pub y2: i32,
+    pub z2: i32,
+}
+
+impl Line3d {
+    pub fn is_axis_aligned(&self) -> bool {
+        let num_changing = [self.x1 != self.x2, self.y1 != self.y2, self.z1 != self.z2]
+            .iter()
+            .filter(|changes| **changes)
+            .count();
+
+        num_changing <= 1
+    }
+}
+
+type Point3d = (i128, i128, i128);
+
+// Same idea as the 2d carriers: lattice points on a line are base + k * direction for a primitive
+// direction, and picking the base with 0 <= base . direction < |direction|² makes it unique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Carrier3d {
+    direction: Point3d,
+    base: Point3d,
+}
+
+impl Carrier3d {
+    fn through(line: &Line3d) -> Self {
+        let start = (line.x1 as i64, line.y1 as i64, line.z1 as i64);
+        let (dx, dy, dz) = (line.x2 as i64 - start.0, line.y2 as i64 - start.1, line.z2 as i64 - start.2);
+        let g = gcd(gcd(dx.abs(), dy.abs()), dz.abs());
+
+        let (a, b, c) = match (dx, dy, dz) {
+            (0, 0, 0) => (1, 0, 0),
+            _ if (dx, dy, dz) < (0, 0, 0) => (-dx / g, -dy / g, -dz / g),
+            _ => (dx / g, dy / g, dz / g),
+        };
+
+        let direction = (a as i128, b as i128, c as i128);
+        let start = (start.0 as i128, start.1 as i128, start.2 as i128);
+        let k = dot(start, direction).div_euclid(dot(direction, direction));
+
+        Carrier3d {
+            direction,
+            base: (start.0 - k * direction.0, start.1 - k * direction.1, start.2 - k * direction.2),
+        }
+    }
+
+    fn index_of(&self, point: Point3d) -> i64 {
+        dot(point, self.direction).div_euclid(dot(self.direction, self.direction)) as i64
+    }
+
+    fn point_at(&self, index: i64) -> Point3d {
+        let (k, (a, b, c)) = (index as i128, self.direction);
+        (self.base.0 + k * a, self.base.1 + k * b, self.base.2 + k * c)
+    }
+
+    fn contains(&self, point: Point3d) -> bool {
+        self.point_at(self.index_of(point)) == point
+    }
+
+    // The lattice point both carriers go through, if any. Solved in the coordinate plane where the
+    // directions are independent, which keeps the products within i128.
+    fn crossing(&self, other: &Carrier3d) -> Option<Point3d> {
+        let (d1, d2) = (self.direction, other.direction);
+        let w = (other.base.0 - self.base.0, other.base.1 - self.base.1, other.base.2 - self.base.2);
+
+        let planes = [
+            (d1.0, d1.1, d2.0, d2.1, w.0, w.1),
+            (d1.1, d1.2, d2.1, d2.2, w.1, w.2),
+            (d1.0, d1.2, d2.0, d2.2, w.0, w.2),
+        ];
+        let (u1, v1, u2, v2, wu, wv) = planes.into_iter().find(|(u1, v1, u2, v2, _, _)| u1 * v2 - v1 * u2 != 0)?;
+
+        let det = u1 * v2 - v1 * u2;
+        let numerator = wu * v2 - wv * u2;
+
+        if numerator % det != 0 {
+            return None;
+        }
+
+        let s = numerator / det;
+        let point = (self.base.0 + s * d1.0, self.base.1 + s * d1.1, self.base.2 + s * d1.2);
+
+        other.contains(point).then_some(point)
+    }
+}
+
+fn dot(p: Point3d, q: Point3d) -> i128 {
+    p.0 * q.0 + p.1 * q.1 + p.2 * q.2
+}
+
+// Collinear segments are merged into runs per carrier, so only points where different carriers
+// cross need to be looked at individually
+pub fn count_overlaps_3d(lines: &[Line3d], allow_diagonals: bool, min_coverage: usize) -> u64 {
+    let mut carrier_ids: HashMap<Carrier3d, usize> = HashMap::new();
+    let mut carriers: Vec<Carrier3d> = vec![];
+    let mut intervals: Vec<Vec<(i64, i64)>> = vec![];
+
+    for line in lines.iter().filter(|line| allow_diagonals || line.is_axis_aligned()) {
+        let carrier = Carrier3d::through(line);
+        let id = *carrier_ids.entry(carrier).or_insert(carriers.len());
+
+        if id == carriers.len() {
+            carriers.push(carrier);
+            intervals.push(vec![]);
+        }
+
+        let start = carrier.index_of((line.x1 as i128, line.y1 as i128, line.z1 as i128));
+        let end = carrier.index_of((line.x2 as i128, line.y2 as i128, line.z2 as i128));
+        intervals[id].push((start.min(end), start.max(end)));
+    }
+
+    let runs = intervals.iter()
+        .enumerate()
+        .flat_map(|(id, intervals)| merge_intervals(id, intervals))
+        .collect::<Vec<_>>();
+
+    count_covered(&runs, find_crossings(&carriers, &runs).values(), min_coverage)
+}
+
+// Runs sorted by their lowest x, only pairs with overlapping bounding boxes are intersected. Unlike
+// the 2d sweep this stays O(runs²) in the worst case, when most runs share a range of x.
+fn find_crossings(carriers: &[Carrier3d], runs: &[Run]) -> HashMap<Point3d, Vec<(usize, usize)>> {
+    let bounds = runs.iter()
+        .map(|run| {
+            let carrier = &carriers[run.carrier];
+            let (first, last) = (carrier.point_at(run.start), carrier.point_at(run.end));
+
+            (
+                (first.0.min(last.0), first.1.min(last.1), first.2.min(last.2)),
+                (first.0.max(last.0), first.1.max(last.1), first.2.max(last.2)),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut order = (0..runs.len()).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|i| bounds[*i].0 .0);
+
+    let mut crossings: HashMap<Point3d, Vec<(usize, usize)>> = HashMap::new();
+    let mut active: Vec<usize> = vec![];
+
+    for i in order {
+        let (min, max) = bounds[i];
+        active.retain(|j| bounds[*j].1 .0 >= min.0);
+
+        for &j in &active {
+            let (other_min, other_max) = bounds[j];
+
+            if other_max.1 < min.1 || max.1 < other_min.1 || other_max.2 < min.2 || max.2 < other_min.2 {
+                continue;
+            }
+
+            let (first, second) = (&carriers[runs[i].carrier], &carriers[runs[j].carrier]);
+
+            let point = match first.crossing(second) {
+                Some(point) => point,
+                None => continue,
+            };
+
+            let on_runs = (first.index_of(point), second.index_of(point));
+
+            if runs[i].start <= on_runs.0 && on_runs.0 <= runs[i].end && runs[j].start <= on_runs.1 && on_runs.1 <= runs[j].end {
+                let contributions = crossings.entry(point).or_default();
+
+                for run in [&runs[i], &runs[j]] {
+                    if !contributions.iter().any(|(carrier, _)| *carrier == run.carrier) {
+                        contributions.push((run.carrier, run.count));
+                    }
+                }
+            }
+        }
+
+        active.push(i);
+    }
+
+    crossings
+}
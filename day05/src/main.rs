@@ -1,22 +1,124 @@
-use std::collections::HashMap;
+mod crossings;
+mod line;
+mod line3d;
+mod render;
+mod status;
+mod sweep;
+mod vent_field;
+
+use std::io::Write;
+
+use line::{Line, Rasterisation};
+use line3d::{count_overlaps_3d, Line3d};
+use render::{BoundingBox, Heatmap};
+use vent_field::VentField;
 
 fn main() {
     let input = include_str!("../resources/input");
     let lines = parse_lines(input);
-    
-    let intersections = find_intersections(&lines, false);
-    println!("[1/2] Result: {}", intersections.len());
 
-    let intersections = find_intersections(&lines, true);
-    println!("[2/2] Result: {}", intersections.len());
+    let rasterisation = if std::env::args().any(|arg| arg == "--bresenham") {
+        Rasterisation::Bresenham
+    } else {
+        Rasterisation::Exact
+    };
+
+    // --axis-aligned leaves out the diagonal lines for render and query
+    let allow_diagonals = !std::env::args().any(|arg| arg == "--axis-aligned");
+
+    let args: Vec<String> = std::env::args().filter(|arg| arg != "--bresenham" && arg != "--axis-aligned").collect();
+
+    if args.get(1).map(String::as_str) == Some("render") {
+        let lines = lines.into_iter().filter(|line| allow_diagonals || line.is_axis_aligned()).collect::<Vec<_>>();
+        render(&lines, &args[2..], rasterisation);
+        return;
+    }
+
+    if let (Some("3d"), Some(path)) = (args.get(1).map(String::as_str), args.get(2)) {
+        let lines = parse_lines_3d(&std::fs::read_to_string(path).unwrap());
+        println!("[1/2] Result: {}", count_overlaps_3d(&lines, false, 2));
+        println!("[2/2] Result: {}", count_overlaps_3d(&lines, true, 2));
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("query") {
+        query(VentField::new(&lines, allow_diagonals, rasterisation), &args[2..]);
+        return;
+    }
+
+    println!("[1/2] Result: {}", count_overlaps(&lines, false, rasterisation, 2));
+    println!("[2/2] Result: {}", count_overlaps(&lines, true, rasterisation, 2));
 }
 
-#[derive(Debug)]
-struct Line {
-    x1: i32,
-    y1: i32,
-    x2: i32,
-    y2: i32,
+// render [ascii|pgm|ppm] [min_x min_y max_x max_y]
+fn render(lines: &[Line], args: &[String], rasterisation: Rasterisation) {
+    let (format, args) = match args.first().map(String::as_str) {
+        Some(format @ ("ascii" | "pgm" | "ppm")) => (Some(format), &args[1..]),
+        _ => (None, args),
+    };
+
+    let numbers = args.iter().map(|arg| arg.parse::<i32>()).collect::<Result<Vec<_>, _>>();
+
+    let bounds = match numbers.as_deref() {
+        Ok([]) => BoundingBox::around(lines).unwrap(),
+        Ok([min_x, min_y, max_x, max_y]) if min_x <= max_x && min_y <= max_y => BoundingBox { min_x: *min_x, min_y: *min_y, max_x: *max_x, max_y: *max_y },
+        _ => {
+            eprintln!("Usage: render [ascii|pgm|ppm] [min_x min_y max_x max_y]");
+            return;
+        }
+    };
+
+    let heatmap = Heatmap::new(lines, bounds, rasterisation);
+
+    if heatmap.scale() > 1 {
+        eprintln!("Binned {0}x{0} points per pixel", heatmap.scale());
+    }
+
+    // the text map is only readable for small areas
+    let format = format.unwrap_or(if bounds.width() <= 100 && bounds.height() <= 100 { "ascii" } else { "pgm" });
+
+    let output = match format {
+        "ppm" => heatmap.to_ppm(),
+        "pgm" => heatmap.to_pgm(),
+        _ => format!("{}\n", heatmap).into_bytes(),
+    };
+
+    std::io::stdout().write_all(&output).unwrap();
+}
+
+// query [at x y | atleast k | max | within min_x min_y max_x max_y]
+fn query(field: VentField, args: &[String]) {
+    let numbers = args.iter().skip(1).map(|arg| arg.parse::<i32>().unwrap()).collect::<Vec<_>>();
+
+    match (args.first().map(String::as_str), &numbers[..]) {
+        (Some("at"), [x, y]) => {
+            let coverage = field.coverage_at((*x, *y));
+            println!("{},{}: {} line(s){}", x, y, coverage, if coverage == 0 { " (safe)" } else { "" });
+        }
+        (Some("atleast"), [min_coverage]) => {
+            for (x, y) in field.points_at_least(*min_coverage as usize) {
+                println!("{},{}", x, y);
+            }
+        }
+        (Some("max"), []) => {
+            let (max_coverage, points) = field.max_coverage();
+            println!("Max coverage: {} at {} point(s)", max_coverage, points.len());
+
+            for (x, y) in points {
+                println!("{},{}", x, y);
+            }
+        }
+        (Some("within"), [min_x, min_y, max_x, max_y]) => {
+            let bounds = BoundingBox { min_x: *min_x, min_y: *min_y, max_x: *max_x, max_y: *max_y };
+            let mut points = field.coverage_within(&bounds).into_iter().collect::<Vec<_>>();
+            points.sort_unstable();
+
+            for ((x, y), coverage) in points {
+                println!("{},{}: {}", x, y, coverage);
+            }
+        }
+        _ => panic!("Invalid query {:?}", args),
+    }
 }
 
 fn parse_lines(input: &str) -> Vec<Line> {
@@ -24,96 +126,63 @@ fn parse_lines(input: &str) -> Vec<Line> {
     
     let mut lines = vec![];
 
-    loop {
-        match (parts.next(), parts.next(), parts.next()) {
-            (Some(start), Some(_), Some(end)) => {
-                let mut start = start.split(',');
-                let mut end = end.split(',');
-
-                lines.push(
-                    Line {
-                        x1: start.next().unwrap().parse::<i32>().unwrap(),
-                        y1: start.next().unwrap().parse::<i32>().unwrap(),
-                        x2: end.next().unwrap().parse::<i32>().unwrap(),
-                        y2: end.next().unwrap().parse::<i32>().unwrap()
-                    }
-                );
-                
-            },
-            _ => break
-        }
+    while let (Some(start), Some(_), Some(end)) = (parts.next(), parts.next(), parts.next()) {
+        let mut start = start.split(',');
+        let mut end = end.split(',');
+
+        lines.push(
+            Line {
+                x1: start.next().unwrap().parse::<i32>().unwrap(),
+                y1: start.next().unwrap().parse::<i32>().unwrap(),
+                x2: end.next().unwrap().parse::<i32>().unwrap(),
+                y2: end.next().unwrap().parse::<i32>().unwrap()
+            }
+        );
     }
 
     lines
 }
 
-fn find_intersections(lines: &Vec<Line>, allow_diagonals: bool) -> Vec<(i32, i32)> {
-    let mut points: HashMap<(i32, i32), usize> = HashMap::new();
+fn parse_lines_3d(input: &str) -> Vec<Line3d> {
+    let mut parts = input.split_ascii_whitespace();
 
-    for line in lines {
-    
-        if line.y1 == line.y2 {     // horizontal
-            let (start, end) = if line.x1.min(line.x2) == line.x1 {
-                (line.x1, line.x2)
-            } else {
-                (line.x2, line.x1)
-            };
-
-            for x in start..(end + 1) {
-                *points.entry((x, line.y1)).or_insert(0) += 1;
-            }
-        } else if line.x1 == line.x2 {   // vertical
-            let (start, end) = if line.y1.min(line.y2) == line.y1 {
-                (line.y1, line.y2)
-            } else {
-                (line.y2, line.y1)
-            };
-
-            for y in start..(end + 1) {
-                *points.entry((line.x1, y)).or_insert(0) += 1;
-            }
-        } else if allow_diagonals { // diagonal
-            let (x_start, x_end) = if line.x1.min(line.x2) == line.x1 {
-                (line.x1, line.x2)
-            } else {
-                (line.x2, line.x1)
-            };
-
-            let (y_start, y_end) = if line.x1 == x_start {
-                (line.y1, line.y2)
-            } else {
-                (line.y2, line.y1)
-            };
-
-            let k = if (y_end - y_start) > 0 {
-                1
-            } else {
-                -1
-            };
-
-            for (i, x) in (x_start..(x_end + 1)).enumerate() {
-                *points.entry((x, y_start + (i as i32 * k))).or_insert(0) += 1;
-            }
+    let mut lines = vec![];
+
+    while let (Some(start), Some(_), Some(end)) = (parts.next(), parts.next(), parts.next()) {
+        let start = start.split(',').map(|v| v.parse::<i32>().unwrap()).collect::<Vec<_>>();
+        let end = end.split(',').map(|v| v.parse::<i32>().unwrap()).collect::<Vec<_>>();
+
+        match (&start[..], &end[..]) {
+            ([x1, y1, z1], [x2, y2, z2]) => lines.push(
+                Line3d { x1: *x1, y1: *y1, z1: *z1, x2: *x2, y2: *y2, z2: *z2 }
+            ),
+            _ => panic!("Invalid 3d segment {:?} -> {:?}", start, end),
         }
     }
 
-    let intersections: Vec<(i32, i32)> = points.into_iter()
-        .filter_map(
-            |(point, crossings)| 
-                if crossings > 1 {
-                    Some(point)
-                } else {
-                    None
-                }
-        )
-        .collect();
-
-    intersections
+    lines
+}
+
+fn count_overlaps(lines: &[Line], allow_diagonals: bool, rasterisation: Rasterisation, min_coverage: usize) -> u64 {
+    VentField::new(lines, allow_diagonals, rasterisation).count_at_least(min_coverage)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+
+    use crate::vent_field::count_points;
+
+    // Coordinates in min..=max from a small LCG, the same sequence on every run
+    fn random_coordinates(seed: u64, min: i32, max: i32) -> impl FnMut() -> i32 {
+        let mut state = seed;
+
+        move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            min + ((state >> 33) % (max - min + 1) as u64) as i32
+        }
+    }
 
     const TEST_DATA:&str = "
         0,9 -> 5,9
@@ -131,16 +200,230 @@ mod tests {
     #[test]
     fn test_part1() {
         let lines = parse_lines(TEST_DATA);
-        let intersections = find_intersections(&lines, false);
-        
-        assert_eq!(5, intersections.len());
+        let num_overlaps = count_overlaps(&lines, false, Rasterisation::Exact, 2);
+
+        assert_eq!(5, num_overlaps);
     }
 
     #[test]
     fn test_part2() {
         let lines = parse_lines(TEST_DATA);
-        let intersections = find_intersections(&lines, true);
-        
-        assert_eq!(12, intersections.len());
+        let num_overlaps = count_overlaps(&lines, true, Rasterisation::Exact, 2);
+
+        assert_eq!(12, num_overlaps);
+    }
+
+    #[test]
+    fn test_arbitrary_slopes() {
+        let line = Line { x1: 0, y1: 0, x2: 6, y2: 3 };
+        assert_eq!(vec![(0, 0), (2, 1), (4, 2), (6, 3)], line.points(Rasterisation::Exact));
+        assert_eq!(7, line.points(Rasterisation::Bresenham).len());
+
+        // the span doesn't fit into an i32
+        let line = Line { x1: -2_000_000_000, y1: 0, x2: 2_000_000_000, y2: 2 };
+        assert_eq!(vec![(-2_000_000_000, 0), (0, 1), (2_000_000_000, 2)], line.points(Rasterisation::Exact));
+        assert_eq!(vec![(0, 1)], line.points_within(Rasterisation::Exact, (-1, -1), (1, 1)));
+
+        let crossing = Line { x1: 0, y1: -2_000_000_000, x2: 0, y2: 2_000_000_000 };
+        assert_eq!(1, count_overlaps(&[line, crossing], true, Rasterisation::Exact, 2));
+
+        for line in parse_lines("0,0 -> 9,4\n9,4 -> 0,0\n-6,-6 -> -4,-5\n2,9 -> 5,-3\n7,1 -> -5,4") {
+            let points = line.points(Rasterisation::Bresenham);
+            let within = points.iter().copied().filter(|(x, y)| (0..=6).contains(x) && (-1..=3).contains(y)).collect::<Vec<_>>();
+            assert_eq!(within, line.points_within(Rasterisation::Bresenham, (0, -1), (6, 3)));
+        }
+
+        let line = Line { x1: -6, y1: -6, x2: -4, y2: -5 };
+        assert_eq!(vec![(-6, -6), (-5, -5), (-4, -5)], line.points(Rasterisation::Bresenham));
+
+        let line = Line { x1: 3, y1: 7, x2: 3, y2: 7 };
+        assert_eq!(vec![(3, 7)], line.points(Rasterisation::Exact));
+        assert_eq!(vec![(3, 7)], line.points(Rasterisation::Bresenham));
+
+        let lines = parse_lines("
+            0,0 -> 6,3
+            4,0 -> 4,4
+            6,0 -> 0,3
+        ");
+
+        let mut overlaps = count_points(&lines, Rasterisation::Exact).into_iter()
+            .filter(|(_, crossings)| *crossings > 1)
+            .map(|(point, _)| point)
+            .collect::<Vec<_>>();
+        overlaps.sort_unstable();
+        assert_eq!(vec![(4, 1), (4, 2)], overlaps);
+    }
+
+    #[test]
+    fn test_sweep_matches_point_counting() {
+        let mut lines = parse_lines(TEST_DATA);
+        lines.extend(parse_lines("
+            0,0 -> 9,6
+            3,0 -> 3,9
+            12,8 -> 0,0
+            1,1 -> 1,1
+            -4,5 -> 10,5
+            6,6 -> 6,-2
+        "));
+
+        for allow_diagonals in [false, true] {
+            let points = count_points(
+                &lines.iter().filter(|line| allow_diagonals || line.is_axis_aligned()).copied().collect::<Vec<_>>(),
+                Rasterisation::Exact,
+            );
+
+            for min_coverage in 1..5 {
+                let expected = points.values().filter(|crossings| **crossings >= min_coverage).count() as u64;
+                assert_eq!(expected, count_overlaps(&lines, allow_diagonals, Rasterisation::Exact, min_coverage));
+            }
+        }
+
+        // arbitrary slopes through a small grid give many crossings of three and more runs
+        let mut next = random_coordinates(7, -4, 19);
+        let lines = (0..300).map(|_| Line { x1: next(), y1: next(), x2: next(), y2: next() }).collect::<Vec<_>>();
+        let points = count_points(&lines, Rasterisation::Exact);
+
+        for min_coverage in 1..8 {
+            let expected = points.values().filter(|crossings| **crossings >= min_coverage).count() as u64;
+            assert_eq!(expected, count_overlaps(&lines, true, Rasterisation::Exact, min_coverage));
+        }
+
+        let lines = parse_lines("
+            0,0 -> 2000000,0
+            1000000,-1000000 -> 1000000,1000000
+            500000,0 -> 2500000,0
+            0,-3000000 -> 3000000,3000000
+        ");
+
+        assert_eq!(1500002, count_overlaps(&lines, true, Rasterisation::Exact, 2));
+        assert_eq!(2, count_overlaps(&lines, true, Rasterisation::Exact, 3));
+    }
+
+    #[test]
+    fn test_render() {
+        let lines = parse_lines(TEST_DATA);
+        let heatmap = Heatmap::new(&lines, BoundingBox::around(&lines).unwrap(), Rasterisation::Exact);
+
+        let expected = "
+            1.1....11.
+            .111...2..
+            ..2.1.111.
+            ...1.2.2..
+            .112313211
+            ...1.2....
+            ..1...1...
+            .1.....1..
+            1.......1.
+            222111....
+        ";
+        let expected = expected.split_whitespace().collect::<Vec<_>>().join("\n");
+        assert_eq!(expected, heatmap.to_string());
+
+        let cropped = Heatmap::new(&lines, BoundingBox { min_x: 3, min_y: 3, max_x: 5, max_y: 4 }, Rasterisation::Exact);
+        assert_eq!("1.2\n231", cropped.to_string());
+        assert_eq!(b"P5\n3 2\n255\n", &cropped.to_pgm()[..11]);
+        assert_eq!(11 + 3 * 6, cropped.to_ppm().len());
+
+        // large areas are binned instead of allocating a pixel per point
+        let diagonal = parse_lines("0,0 -> 9999,9999");
+        let binned = Heatmap::new(&diagonal, BoundingBox::around(&diagonal).unwrap(), Rasterisation::Exact);
+        assert_eq!(5, binned.scale());
+        assert_eq!(b"P5\n2000 2000\n255\n", &binned.to_pgm()[..17]);
+        assert_eq!(5, binned.max_count());
+
+        let everything = BoundingBox { min_x: i32::MIN, min_y: i32::MIN, max_x: i32::MAX, max_y: i32::MAX };
+        let binned = Heatmap::new(&parse_lines("0,0 -> 9,0"), everything, Rasterisation::Exact);
+        assert_eq!(1 << 21, binned.scale());
+        assert_eq!(10, binned.max_count());
+
+        // counted per bin without visiting the points, 9 * 2^21 * 255 doesn't fit into a u32
+        let wide = parse_lines(&"-2000000000,0 -> 2000000000,0\n".repeat(9));
+        let binned = Heatmap::new(&wide, everything, Rasterisation::Exact);
+        assert_eq!(9 << 21, binned.max_count());
+        assert_eq!(Some(&255), binned.to_pgm().iter().max());
+
+        let binned = Heatmap::new(&parse_lines(&"0,0 -> 2097151,0\n".repeat(9)), everything, Rasterisation::Bresenham);
+        assert_eq!(9 << 21, binned.max_count());
+        assert_eq!(Some(&255), binned.to_pgm().iter().max());
+    }
+
+    #[test]
+    fn test_vent_field_queries() {
+        let field = VentField::new(&parse_lines(TEST_DATA), true, Rasterisation::Exact);
+
+        assert_eq!(12, field.count_at_least(2));
+        assert_eq!(12, field.points_at_least(2).len());
+        assert_eq!(vec![(4, 4), (6, 4)], field.points_at_least(3));
+        assert_eq!((3, vec![(4, 4), (6, 4)]), field.max_coverage());
+
+        assert_eq!(2, field.coverage_at((2, 2)));
+        assert_eq!(0, field.coverage_at((9, 9)));
+        assert_eq!(3, field.coverage_at((6, 4)));
+
+        let within = field.coverage_within(&BoundingBox { min_x: 3, min_y: 3, max_x: 5, max_y: 4 });
+        assert_eq!(5, within.len());
+        assert_eq!(Some(&3), within.get(&(4, 4)));
+        assert_eq!(None, within.get(&(4, 3)));
+
+        // the same options as the puzzle answers: no diagonals, or Bresenham steps
+        let field = VentField::new(&parse_lines(TEST_DATA), false, Rasterisation::Exact);
+        assert_eq!(5, field.count_at_least(2));
+        assert_eq!(1, field.coverage_at((2, 2)));
+
+        let lines = parse_lines("0,0 -> 4,2 0,1 -> 4,1");
+        let field = VentField::new(&lines, true, Rasterisation::Bresenham);
+        assert_eq!(vec![(1, 1), (2, 1)], field.points_at_least(2));
+        assert_eq!(2, field.coverage_at((1, 1)));
+        assert_eq!(Some(&2), field.coverage_within(&BoundingBox { min_x: 0, min_y: 0, max_x: 1, max_y: 1 }).get(&(1, 1)));
+        assert_eq!(vec![(2, 1)], VentField::new(&lines, true, Rasterisation::Exact).points_at_least(2));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_3d() {
+        let lines = parse_lines_3d("
+            0,0,0 -> 4,0,0
+            2,-2,0 -> 2,2,0
+            2,0,-3 -> 2,0,3
+            0,0,0 -> 4,4,4
+            4,0,0 -> 0,4,4
+            0,0,1 -> 4,0,1
+        ");
+
+        // flattened, the last segment would lie on top of the first one
+        assert_eq!(2, count_overlaps_3d(&lines, false, 2));
+        assert_eq!(1, count_overlaps_3d(&lines, false, 3));
+        assert_eq!(5, count_overlaps_3d(&lines, true, 2));
+
+        // compared with counting every lattice point of arbitrary segments in a small cube
+        let mut next = random_coordinates(11, -2, 6);
+        let lines = (0..400)
+            .map(|_| Line3d { x1: next(), y1: next(), z1: next(), x2: next(), y2: next(), z2: next() })
+            .collect::<Vec<_>>();
+
+        let mut points: HashMap<(i32, i32, i32), usize> = HashMap::new();
+
+        for line in &lines {
+            let (dx, dy, dz) = (line.x2 - line.x1, line.y2 - line.y1, line.z2 - line.z1);
+            let steps = [dx, dy, dz].iter().fold(0, |g, d| line::gcd(g, d.abs() as i64)) as i32;
+
+            for i in 0..=steps {
+                let point = (line.x1 + i * dx / steps.max(1), line.y1 + i * dy / steps.max(1), line.z1 + i * dz / steps.max(1));
+                *points.entry(point).or_insert(0) += 1;
+            }
+        }
+
+        for min_coverage in 1..6 {
+            let expected = points.values().filter(|count| **count >= min_coverage).count() as u64;
+            assert_eq!(expected, count_overlaps_3d(&lines, true, min_coverage));
+        }
+
+        let lines = parse_lines_3d("
+            -2000000000,0,0 -> 2000000000,0,0
+            -1000000000,-1000000000,-1000000000 -> 1000000000,1000000000,1000000000
+            0,0,-2000000000 -> 0,0,2000000000
+            7,-2000000000,0 -> 7,2000000000,0
+        ");
+        assert_eq!(2, count_overlaps_3d(&lines, true, 2));
+        assert_eq!(1, count_overlaps_3d(&lines, true, 3));
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::line::{Line, Rasterisation};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl BoundingBox {
+    pub fn around(lines: &[Line]) -> Option<Self> {
+        let xs = lines.iter().flat_map(|line| [line.x1, line.x2]);
+        let ys = lines.iter().flat_map(|line| [line.y1, line.y2]);
+
+        Some(BoundingBox {
+            min_x: xs.clone().min()?,
+            min_y: ys.clone().min()?,
+            max_x: xs.max()?,
+            max_y: ys.max()?,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max_x as i64 - self.min_x as i64 + 1).max(0) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max_y as i64 - self.min_y as i64 + 1).max(0) as usize
+    }
+}
+
+// Larger areas are binned so that neither side of the heatmap exceeds this many pixels
+pub const MAX_RESOLUTION: usize = 2048;
+
+pub struct Heatmap {
+    width: usize,
+    height: usize,
+    // each pixel sums up the coverage of scale x scale points
+    scale: usize,
+    counts: Vec<u64>,
+}
+
+impl Heatmap {
+    pub fn new(lines: &[Line], bounds: BoundingBox, rasterisation: Rasterisation) -> Self {
+        let scale = bounds.width().max(bounds.height()).div_ceil(MAX_RESOLUTION).max(1);
+        let (width, height) = (bounds.width().div_ceil(scale), bounds.height().div_ceil(scale));
+
+        let mut counts = vec![0u64; width * height];
+        let (min, max) = ((bounds.min_x, bounds.min_y), (bounds.max_x, bounds.max_y));
+
+        // the points of a line within one bin are consecutive steps, so the walk only visits
+        // the bins the line crosses
+        for line in lines {
+            let steps = line.steps(rasterisation);
+            let (mut i, last) = steps.clip(min, max, (0, steps.last()));
+
+            while i <= last {
+                let (x, y) = steps.point(i);
+                let column = (x as i64 - bounds.min_x as i64) as usize / scale;
+                let row = (y as i64 - bounds.min_y as i64) as usize / scale;
+
+                let left = bounds.min_x as i64 + (column * scale) as i64;
+                let top = bounds.min_y as i64 + (row * scale) as i64;
+                let right = (left + scale as i64 - 1).min(bounds.max_x as i64);
+                let bottom = (top + scale as i64 - 1).min(bounds.max_y as i64);
+                let (_, end) = steps.clip((left as i32, top as i32), (right as i32, bottom as i32), (i, last));
+
+                counts[row * width + column] += (end - i + 1) as u64;
+                i = end + 1;
+            }
+        }
+
+        Heatmap {
+            width,
+            height,
+            scale,
+            counts,
+        }
+    }
+
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    pub fn max_count(&self) -> u64 {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    // Binary greyscale image, brightness scaled to the highest coverage
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let max_count = self.max_count().max(1);
+
+        let mut image = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        image.extend(self.counts.iter().map(|count| (count * 255 / max_count) as u8));
+        image
+    }
+
+    // Binary colour image, coverage mapped from dark blue over red to yellow
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let max_count = self.max_count().max(1);
+
+        let mut image = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        image.extend(self.counts.iter().flat_map(|count| heat_color(*count, max_count)));
+        image
+    }
+}
+
+fn heat_color(count: u64, max_count: u64) -> [u8; 3] {
+    if count == 0 {
+        return [0, 0, 0];
+    }
+
+    let t = count as f64 / max_count as f64;
+    let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    [channel(2.0 * t), channel(2.0 * t - 1.0), channel(1.0 - 2.0 * t)]
+}
+
+// Same layout as the puzzle description: '.' for no line, otherwise the number of lines
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = self.counts
+            .chunks(self.width.max(1))
+            .map(|row| row.iter()
+                .map(|count| match count {
+                    0 => '.',
+                    1..=9 => char::from_digit(*count as u32, 10).unwrap(),
+                    _ => '#',
+                })
+                .collect::<String>())
+            .collect::<Vec<String>>();
+
+        write!(
+            f, "{}",
+            lines.join("\n")
+        )
+    }
+}
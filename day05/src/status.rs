@@ -0,0 +1,120 @@
+pub type Tree = Option<usize>;
+
+struct Node {
+    value: usize,
+    priority: u64,
+    left: Tree,
+    right: Tree,
+}
+
+// Treap of segment ids in sweep order. The order itself is only known to the caller, which
+// splits with a predicate that holds for a prefix of the sequence.
+#[derive(Default)]
+pub struct Status {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    seed: u64,
+}
+
+impl Status {
+    fn allocate(&mut self, value: usize) -> usize {
+        // SplitMix64 step, only used to balance the tree
+        self.seed = self.seed.wrapping_add(0x9e3779b97f4a7c15);
+        let mut priority = self.seed;
+        priority = (priority ^ (priority >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        priority = (priority ^ (priority >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+        let node = Node { value, priority: priority ^ (priority >> 31), left: None, right: None };
+
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // (values for which goes_left holds, the rest)
+    pub fn split(&mut self, tree: Tree, goes_left: &mut impl FnMut(usize) -> bool) -> (Tree, Tree) {
+        let node = match tree {
+            Some(node) => node,
+            None => return (None, None),
+        };
+
+        if goes_left(self.nodes[node].value) {
+            let (left, right) = self.split(self.nodes[node].right, goes_left);
+            self.nodes[node].right = left;
+            (Some(node), right)
+        } else {
+            let (left, right) = self.split(self.nodes[node].left, goes_left);
+            self.nodes[node].left = right;
+            (left, Some(node))
+        }
+    }
+
+    pub fn merge(&mut self, first: Tree, second: Tree) -> Tree {
+        match (first, second) {
+            (None, tree) | (tree, None) => tree,
+            (Some(a), Some(b)) if self.nodes[a].priority > self.nodes[b].priority => {
+                self.nodes[a].right = self.merge(self.nodes[a].right, second);
+                first
+            }
+            (Some(_), Some(b)) => {
+                self.nodes[b].left = self.merge(first, self.nodes[b].left);
+                second
+            }
+        }
+    }
+
+    pub fn build(&mut self, values: &[usize]) -> Tree {
+        values.iter().fold(None, |tree, value| {
+            let node = self.allocate(*value);
+            self.merge(tree, Some(node))
+        })
+    }
+
+    pub fn first(&self, tree: Tree) -> Option<usize> {
+        let mut node = tree?;
+
+        while let Some(left) = self.nodes[node].left {
+            node = left;
+        }
+
+        Some(self.nodes[node].value)
+    }
+
+    pub fn last(&self, tree: Tree) -> Option<usize> {
+        let mut node = tree?;
+
+        while let Some(right) = self.nodes[node].right {
+            node = right;
+        }
+
+        Some(self.nodes[node].value)
+    }
+
+    // Values in order, the nodes are reused afterwards
+    pub fn drain(&mut self, tree: Tree) -> Vec<usize> {
+        let mut values = vec![];
+        let mut stack = vec![];
+        let mut current = tree;
+
+        while current.is_some() || !stack.is_empty() {
+            while let Some(node) = current {
+                stack.push(node);
+                current = self.nodes[node].left;
+            }
+
+            let node = stack.pop().unwrap();
+            values.push(self.nodes[node].value);
+            current = self.nodes[node].right;
+            self.free.push(node);
+        }
+
+        values
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use crate::crossings::find_crossings;
+use crate::line::{gcd, Line};
+
+// All segments lying on the same infinite line share a carrier. Lattice points on a carrier are
+// base + k * (a, b) for a primitive direction (a, b), so collinear segments reduce to intervals of k.
+// Kept in i128: with i32 endpoints the direction alone can need 33 bits and its norm 66.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Carrier {
+    a: i128,
+    b: i128,
+    c: i128,
+    r: i128,
+}
+
+impl Carrier {
+    fn through(line: &Line) -> Self {
+        let (x1, y1) = (line.x1 as i64, line.y1 as i64);
+        let (dx, dy) = (line.x2 as i64 - x1, line.y2 as i64 - y1);
+        let g = gcd(dx.abs(), dy.abs());
+
+        let (a, b) = match (dx, dy) {
+            (0, 0) => (1, 0),
+            _ if dx < 0 || (dx == 0 && dy < 0) => (-dx / g, -dy / g),
+            _ => (dx / g, dy / g),
+        };
+
+        let (a, b, x1, y1) = (a as i128, b as i128, x1 as i128, y1 as i128);
+        let n = a * a + b * b;
+
+        Carrier {
+            a,
+            b,
+            c: b * x1 - a * y1,
+            r: (a * x1 + b * y1).rem_euclid(n),
+        }
+    }
+
+    fn norm(&self) -> i128 {
+        self.a * self.a + self.b * self.b
+    }
+
+    pub fn index_of(&self, point: (i64, i64)) -> i64 {
+        (self.a * point.0 as i128 + self.b * point.1 as i128).div_euclid(self.norm()) as i64
+    }
+
+    pub fn point_at(&self, index: i64) -> (i64, i64) {
+        let n = self.norm();
+        let t = index as i128 * n + self.r;
+
+        (((self.a * t + self.b * self.c) / n) as i64, ((self.b * t - self.a * self.c) / n) as i64)
+    }
+}
+
+// A maximal interval of lattice points on a carrier covered by the same number of lines
+#[derive(Debug, Clone, Copy)]
+pub struct Run {
+    pub carrier: usize,
+    pub start: i64,
+    pub end: i64,
+    pub count: usize,
+}
+
+impl Run {
+    pub fn len(&self) -> u64 {
+        (self.end - self.start + 1) as u64
+    }
+}
+
+pub struct Coverage {
+    carriers: Vec<Carrier>,
+    runs: Vec<Run>,
+    // only points where two or more carriers meet, with the coverage contributed by each carrier
+    crossings: HashMap<(i64, i64), Vec<(usize, usize)>>,
+}
+
+impl Coverage {
+    pub fn new(lines: &[Line]) -> Self {
+        let mut carrier_ids: HashMap<Carrier, usize> = HashMap::new();
+        let mut intervals: Vec<Vec<(i64, i64)>> = vec![];
+
+        for line in lines {
+            let carrier = Carrier::through(line);
+            let next_id = carrier_ids.len();
+            let id = *carrier_ids.entry(carrier).or_insert(next_id);
+
+            if id == intervals.len() {
+                intervals.push(vec![]);
+            }
+
+            let start = carrier.index_of((line.x1 as i64, line.y1 as i64));
+            let end = carrier.index_of((line.x2 as i64, line.y2 as i64));
+            intervals[id].push((start.min(end), start.max(end)));
+        }
+
+        let mut carriers = vec![Carrier { a: 0, b: 0, c: 0, r: 0 }; carrier_ids.len()];
+        for (carrier, id) in carrier_ids {
+            carriers[id] = carrier;
+        }
+
+        let runs = intervals.iter()
+            .enumerate()
+            .flat_map(|(id, intervals)| merge_intervals(id, intervals))
+            .collect::<Vec<_>>();
+
+        let crossings = find_crossings(&carriers, &runs);
+
+        Coverage {
+            carriers,
+            runs,
+            crossings,
+        }
+    }
+
+    pub fn count_at_least(&self, min_coverage: usize) -> u64 {
+        count_covered(&self.runs, self.crossings.values(), min_coverage)
+    }
+
+    pub fn points_at_least(&self, min_coverage: usize) -> Vec<(i64, i64)> {
+        let mut points = self.runs.iter()
+            .filter(|run| run.count >= min_coverage)
+            .flat_map(|run| (run.start..=run.end).map(|index| self.carriers[run.carrier].point_at(index)))
+            .filter(|point| !self.crossings.contains_key(point))
+            .collect::<Vec<_>>();
+
+        points.extend(
+            self.crossings.iter()
+                .filter(|(_, contributions)| total(contributions) >= min_coverage)
+                .map(|(point, _)| *point)
+        );
+
+        points
+    }
+
+    pub fn max_coverage(&self) -> usize {
+        let max_run = self.runs.iter().map(|run| run.count).max().unwrap_or(0);
+        let max_crossing = self.crossings.values().map(|contributions| total(contributions)).max().unwrap_or(0);
+
+        max_run.max(max_crossing)
+    }
+}
+
+fn total(contributions: &[(usize, usize)]) -> usize {
+    contributions.iter().map(|(_, count)| count).sum()
+}
+
+// Points covered by at least min_coverage lines, given the runs and the (carrier, count)
+// contributions at every point where runs of different carriers cross
+pub fn count_covered<'a>(runs: &[Run], crossings: impl IntoIterator<Item=&'a Vec<(usize, usize)>>, min_coverage: usize) -> u64 {
+    let mut result: u64 = runs.iter()
+        .filter(|run| run.count >= min_coverage)
+        .map(Run::len)
+        .sum();
+
+    // each crossing was counted once per carrier on its own; replace that with the combined coverage
+    for contributions in crossings {
+        result -= contributions.iter().filter(|(_, count)| *count >= min_coverage).count() as u64;
+
+        if total(contributions) >= min_coverage {
+            result += 1;
+        }
+    }
+
+    result
+}
+
+pub fn merge_intervals(carrier: usize, intervals: &[(i64, i64)]) -> Vec<Run> {
+    let mut events = intervals.iter()
+        .flat_map(|(start, end)| [(*start, 1i64), (*end + 1, -1i64)])
+        .collect::<Vec<_>>();
+    events.sort_unstable();
+
+    let mut runs: Vec<Run> = vec![];
+    let mut count = 0i64;
+    let mut i = 0;
+
+    while i < events.len() {
+        let position = events[i].0;
+
+        while i < events.len() && events[i].0 == position {
+            count += events[i].1;
+            i += 1;
+        }
+
+        if count == 0 {
+            continue;
+        }
+
+        let end = events[i].0 - 1;
+
+        match runs.last_mut() {
+            Some(last) if last.end + 1 == position && last.count == count as usize => last.end = end,
+            _ => runs.push(Run { carrier, start: position, end, count: count as usize }),
+        }
+    }
+
+    runs
+}
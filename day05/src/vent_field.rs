@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::line::{Line, Rasterisation};
+use crate::render::BoundingBox;
+use crate::sweep::Coverage;
+
+enum Counts {
+    // exact lattice points are shared by collinear segments, so overlaps can be counted per carrier line
+    Carriers(Coverage),
+    Points(HashMap<(i32, i32), usize>),
+}
+
+pub struct VentField {
+    lines: Vec<Line>,
+    rasterisation: Rasterisation,
+    counts: Counts,
+}
+
+impl VentField {
+    pub fn new(lines: &[Line], allow_diagonals: bool, rasterisation: Rasterisation) -> Self {
+        let lines = lines.iter()
+            .filter(|line| allow_diagonals || line.is_axis_aligned())
+            .copied()
+            .collect::<Vec<_>>();
+
+        let counts = match rasterisation {
+            Rasterisation::Exact => Counts::Carriers(Coverage::new(&lines)),
+            Rasterisation::Bresenham => Counts::Points(count_points(&lines, rasterisation)),
+        };
+
+        VentField {
+            lines,
+            rasterisation,
+            counts,
+        }
+    }
+
+    pub fn count_at_least(&self, min_coverage: usize) -> u64 {
+        match &self.counts {
+            Counts::Carriers(coverage) => coverage.count_at_least(min_coverage),
+            Counts::Points(points) => points.values().filter(|count| **count >= min_coverage).count() as u64,
+        }
+    }
+
+    pub fn points_at_least(&self, min_coverage: usize) -> Vec<(i32, i32)> {
+        let mut points = match &self.counts {
+            Counts::Carriers(coverage) => coverage.points_at_least(min_coverage.max(1)).into_iter()
+                .map(|(x, y)| (x as i32, y as i32))
+                .collect::<Vec<_>>(),
+            Counts::Points(points) => points.iter()
+                .filter(|(_, count)| **count >= min_coverage.max(1))
+                .map(|(point, _)| *point)
+                .collect::<Vec<_>>(),
+        };
+
+        points.sort_unstable();
+        points
+    }
+
+    pub fn max_coverage(&self) -> (usize, Vec<(i32, i32)>) {
+        let max_coverage = match &self.counts {
+            Counts::Carriers(coverage) => coverage.max_coverage(),
+            Counts::Points(points) => points.values().copied().max().unwrap_or(0),
+        };
+
+        if max_coverage == 0 {
+            return (0, vec![]);
+        }
+
+        (max_coverage, self.points_at_least(max_coverage))
+    }
+
+    pub fn coverage_at(&self, point: (i32, i32)) -> usize {
+        self.lines.iter().filter(|line| line.contains(point, self.rasterisation)).count()
+    }
+
+    pub fn coverage_within(&self, bounds: &BoundingBox) -> HashMap<(i32, i32), usize> {
+        let (min, max) = ((bounds.min_x, bounds.min_y), (bounds.max_x, bounds.max_y));
+        let mut points: HashMap<(i32, i32), usize> = HashMap::new();
+
+        for line in &self.lines {
+            for point in line.points_within(self.rasterisation, min, max) {
+                *points.entry(point).or_insert(0) += 1;
+            }
+        }
+
+        points
+    }
+}
+
+pub fn count_points(lines: &[Line], rasterisation: Rasterisation) -> HashMap<(i32, i32), usize> {
+    let mut points: HashMap<(i32, i32), usize> = HashMap::new();
+
+    for line in lines {
+        for point in line.points(rasterisation) {
+            *points.entry(point).or_insert(0) += 1;
+        }
+    }
+
+    points
+}
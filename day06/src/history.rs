@@ -0,0 +1,93 @@
+use crate::lifecycle::LifecycleModel;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayCounts {
+    pub day: u32,
+    pub counts_per_timer: Vec<u64>,
+    pub total: u64,
+}
+
+impl DayCounts {
+    pub fn new(model: &LifecycleModel, day: u32, counts_per_state: &[u64]) -> Self {
+        let mut counts_per_timer = vec![0u64; model.max_timer() + 1];
+
+        for (state, count) in counts_per_state.iter().enumerate() {
+            counts_per_timer[model.timer_of(state)] += count;
+        }
+
+        DayCounts {
+            day,
+            total: counts_per_timer.iter().sum(),
+            counts_per_timer,
+        }
+    }
+}
+
+pub fn to_csv(history: &[DayCounts]) -> String {
+    let num_timers = history.first().map_or(0, |counts| counts.counts_per_timer.len());
+
+    let header = ["day".to_string(), "total".to_string()].into_iter()
+        .chain((0..num_timers).map(|timer| format!("timer_{}", timer)))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let rows = history.iter().map(|counts| {
+        [counts.day as u64, counts.total].iter()
+            .chain(counts.counts_per_timer.iter())
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+
+    std::iter::once(header).chain(rows).collect::<Vec<_>>().join("\n")
+}
+
+// Total per day as bars, one column per day (sampled down to `width` columns)
+pub fn ascii_chart(history: &[DayCounts], width: usize, height: usize, log_scale: bool) -> String {
+    if history.is_empty() || width == 0 || height == 0 {
+        return String::new();
+    }
+
+    let scale = |total: u64| if log_scale { (total as f64 + 1.0).ln() } else { total as f64 };
+
+    let columns = (0..width.min(history.len()))
+        .map(|column| &history[column * history.len() / width.min(history.len())])
+        .collect::<Vec<_>>();
+
+    let max_value = columns.iter().map(|counts| scale(counts.total)).fold(0.0, f64::max).max(f64::MIN_POSITIVE);
+    let max_total = columns.iter().map(|counts| counts.total).max().unwrap();
+    let label_width = max_total.to_string().len();
+
+    let mut lines = (0..height).rev()
+        .map(|row| {
+            let label = match row {
+                r if r == height - 1 => max_total.to_string(),
+                0 => "0".to_string(),
+                _ => String::new(),
+            };
+
+            let bars = columns.iter()
+                .map(|counts| {
+                    let filled = (scale(counts.total) / max_value * height as f64).round() as usize;
+                    if filled > row { '#' } else { ' ' }
+                })
+                .collect::<String>();
+
+            format!("{:>width$} |{}", label, bars, width = label_width)
+        })
+        .collect::<Vec<_>>();
+
+    lines.push(format!(
+        "{:>width$} +{}",
+        "", "-".repeat(columns.len()),
+        width = label_width
+    ));
+    lines.push(format!(
+        "{:>width$}  day {} .. {}{}",
+        "", columns.first().unwrap().day, columns.last().unwrap().day,
+        if log_scale { " (log scale)" } else { "" },
+        width = label_width
+    ));
+
+    lines.join("\n")
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifecycleModel {
+    pub reset_timer: usize,
+    pub newborn_timer: usize,
+    // extra days a newborn waits before its timer starts counting down
+    pub maturity_delay: Option<usize>,
+    // fish die after living this many days; fish present at the start count as newborn
+    pub death_age: Option<usize>,
+    pub spawn_count: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Default for LifecycleModel {
+    fn default() -> Self {
+        LifecycleModel {
+            reset_timer: 6,
+            newborn_timer: 8,
+            maturity_delay: None,
+            death_age: None,
+            spawn_count: 1,
+        }
+    }
+}
+
+impl LifecycleModel {
+    // `key = value` per line, `#` starts a comment, unspecified keys keep the puzzle defaults
+    pub fn parse(config: &str) -> Result<Self, ConfigError> {
+        let mut model = LifecycleModel::default();
+
+        for (i, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |reason: String| ConfigError { line: i + 1, reason };
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| error(format!("expected 'key = value', found '{}'", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let number = value.parse::<usize>()
+                .map_err(|_| error(format!("invalid value '{}' for {}", value, key)))?;
+
+            match key {
+                "reset_timer" => model.reset_timer = number,
+                "newborn_timer" => model.newborn_timer = number,
+                "maturity_delay" => model.maturity_delay = Some(number),
+                "death_age" if number == 0 => return Err(error("death_age must be at least 1".to_string())),
+                "death_age" => model.death_age = Some(number),
+                "spawn_count" => model.spawn_count = number as u64,
+                _ => return Err(error(format!("unknown key '{}'", key))),
+            }
+        }
+
+        Ok(model)
+    }
+
+    // Highest timer value any fish can have
+    pub fn max_timer(&self) -> usize {
+        self.reset_timer.max(self.newborn_timer + self.maturity_delay.unwrap_or(0))
+    }
+
+    fn num_timers(&self) -> usize {
+        self.max_timer() + 1
+    }
+
+    fn num_ages(&self) -> usize {
+        self.death_age.unwrap_or(1)
+    }
+
+    pub fn num_states(&self) -> usize {
+        self.num_ages() * self.num_timers()
+    }
+
+    // State of a fish with the given timer at the start of the simulation
+    pub fn initial_state(&self, timer: usize) -> usize {
+        self.state(0, timer)
+    }
+
+    pub fn timer_of(&self, state: usize) -> usize {
+        state % self.num_timers()
+    }
+
+    fn state(&self, age: usize, timer: usize) -> usize {
+        age * self.num_timers() + timer
+    }
+
+    // (from, to, multiplicity) for one day; fish in states without an outgoing parent transition die
+    pub fn transitions(&self) -> Vec<(usize, usize, u64)> {
+        let mut transitions = vec![];
+        let newborn = self.newborn_timer + self.maturity_delay.unwrap_or(0);
+
+        for age in 0..self.num_ages() {
+            let next_age = match self.death_age {
+                Some(death_age) if age + 1 >= death_age => None,
+                Some(_) => Some(age + 1),
+                None => Some(age),
+            };
+
+            for timer in 0..self.num_timers() {
+                let from = self.state(age, timer);
+                let next_timer = if timer == 0 { self.reset_timer } else { timer - 1 };
+
+                if let Some(next_age) = next_age {
+                    transitions.push((from, self.state(next_age, next_timer), 1));
+                }
+
+                if timer == 0 && self.spawn_count > 0 {
+                    transitions.push((from, self.state(0, newborn), self.spawn_count));
+                }
+            }
+        }
+
+        transitions
+    }
+}
@@ -1,55 +1,343 @@
+mod bigint;
+mod history;
+mod lifecycle;
+mod matrix;
+mod population;
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use bigint::BigUint;
+use history::{ascii_chart, to_csv, DayCounts};
+use lifecycle::LifecycleModel;
+use matrix::{Arithmetic, Checked, Exact, Matrix, Modular};
+use population::Population;
+
 fn main() {
     let input = include_str!("../resources/input");
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // --model <path> replaces the puzzle's lifecycle with one loaded from a config file
+    let model = match args.iter().position(|arg| arg == "--model") {
+        Some(i) => {
+            let path = args.remove(i + 1);
+            args.remove(i);
+
+            LifecycleModel::parse(&std::fs::read_to_string(path).unwrap())
+                .unwrap_or_else(|e| panic!("Invalid lifecycle model ({})", e))
+        }
+        None => LifecycleModel::default(),
+    };
+
+    let population = Population::parse(input, &model).unwrap_or_else(|errors| {
+        let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        panic!("Invalid input:\n{}", errors.join("\n"))
+    });
 
-    println!("[1/2] Result: {}", count_entries(input, 80));
-    println!("[1/2] Result: {}", count_entries(input, 256));
+    // history <num_days> [csv|chart|logchart]
+    if args.first().map(String::as_str) == Some("history") {
+        let num_days = args.get(1).map_or(80, |v| v.parse::<u32>().unwrap());
+        let history = count_history(&model, &population, num_days).unwrap_or_else(|e| panic!("{}", e));
+
+        match args.get(2).map(String::as_str) {
+            Some("chart") => println!("{}", ascii_chart(&history, 100, 20, false)),
+            Some("logchart") => println!("{}", ascii_chart(&history, 100, 20, true)),
+            _ => println!("{}", to_csv(&history)),
+        }
+        return;
+    }
+
+    // <num_days> [modulus]: exact count via big integers, or modulo the given number
+    if let Some(num_days) = args.first().map(|v| v.parse::<u64>().unwrap()) {
+        match args.get(1).map(|v| v.parse::<u64>().unwrap()) {
+            Some(modulus) => {
+                let modular = Modular::new(modulus).unwrap_or_else(|| panic!("Invalid modulus {} (expected: a positive number)", modulus));
+                println!("Result: {}", count_entries_fast(&model, &population, num_days, &modular));
+            }
+            None => println!("Result: {}", count_entries_fast(&model, &population, num_days, &Exact)),
+        }
+        return;
+    }
+
+    for (part, num_days) in [(1, 80), (2, 256)] {
+        match count_entries(&model, &population, num_days) {
+            Ok(result) => println!("[{}/2] Result: {}", part, result),
+            Err(e) => println!("[{}/2] Result: {} ({})", part, count_entries_exact(&model, &population, num_days), e),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Overflow {
+    day: u32,
 }
 
-fn count_entries(input: &str, num_days: u32) -> u64 {
-    const NUM_STATES: usize = 9;
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "fish count exceeds u64 on day {}", self.day)
+    }
+}
+
+fn count_entries(model: &LifecycleModel, population: &Population, num_days: u32) -> Result<u64, Overflow> {
+    let counts = simulate(model, population, num_days, |_, _| ())?;
+    Ok(counts.iter().sum())
+}
+
+// Counts for every day from 0 to num_days
+fn count_history(model: &LifecycleModel, population: &Population, num_days: u32) -> Result<Vec<DayCounts>, Overflow> {
+    let mut history = vec![];
+    simulate(model, population, num_days, |day, counts| history.push(DayCounts::new(model, day, counts)))?;
 
-    let mut counts_per_timer = [0u64; NUM_STATES];
+    Ok(history)
+}
 
-    let timers: Vec<usize> = input.trim().split(',').map(|timer| timer.parse::<usize>().unwrap()).collect();
+// Final counts per state, on_day sees the counts of every day from 0 to num_days
+fn simulate(model: &LifecycleModel, population: &Population, num_days: u32, mut on_day: impl FnMut(u32, &[u64])) -> Result<Vec<u64>, Overflow> {
+    let mut counts = population.counts_per_state.clone();
+    on_day(0, &counts);
 
-    for timer in timers {
-        counts_per_timer[timer] += 1;
+    for day in 1..=num_days {
+        let counts_per_state = step(model, &counts.into_iter().map(Some).collect::<Vec<_>>(), &Checked);
+
+        // the total is what gets reported, so it has to fit as well
+        if checked_sum(&counts_per_state).is_none() {
+            return Err(Overflow { day });
+        }
+
+        counts = counts_per_state.iter().map(|count| count.unwrap()).collect();
+        on_day(day, &counts);
     }
 
+    Ok(counts)
+}
+
+fn checked_sum(counts_per_timer: &[Option<u64>]) -> Option<u64> {
+    counts_per_timer.iter().try_fold(0u64, |sum, count| sum.checked_add((*count)?))
+}
+
+fn count_entries_exact(model: &LifecycleModel, population: &Population, num_days: u32) -> BigUint {
+    let mut counts_per_timer = population.counts_per_state.iter().map(|count| BigUint::from(*count)).collect::<Vec<_>>();
+
     for _day in 0..num_days {
-        let mut temp = [0u64; NUM_STATES];
-
-        for i in 0..NUM_STATES - 1  {
-            if i == 0 {
-                temp[6] += counts_per_timer[0];
-                temp[8] += counts_per_timer[0];
-                temp[0] = counts_per_timer[i + 1];
-            } else {
-                temp[i] += counts_per_timer[i + 1];
+        counts_per_timer = step(model, &counts_per_timer, &Exact);
+    }
+
+    counts_per_timer.iter().fold(BigUint::zero(), |sum, count| sum.add(count))
+}
+
+fn step<A: Arithmetic>(model: &LifecycleModel, counts_per_state: &[A::Value], arithmetic: &A) -> Vec<A::Value> {
+    let mut temp = vec![arithmetic.constant(0); model.num_states()];
+
+    for (from, to, multiplicity) in model.transitions() {
+        let moved = if multiplicity == 1 {
+            counts_per_state[from].clone()
+        } else {
+            arithmetic.mul(&arithmetic.constant(multiplicity), &counts_per_state[from])
+        };
+
+        temp[to] = arithmetic.add(&temp[to], &moved);
+    }
+
+    temp
+}
+
+// States that can ever be occupied: with a death age, most (age, timer) pairs are never reached
+fn reachable_states(model: &LifecycleModel, population: &Population) -> Vec<usize> {
+    let mut successors = vec![vec![]; model.num_states()];
+
+    for (from, to, _) in model.transitions() {
+        successors[from].push(to);
+    }
+
+    let mut is_reachable = population.counts_per_state.iter().map(|count| *count > 0).collect::<Vec<_>>();
+    let mut pending = (0..is_reachable.len()).filter(|state| is_reachable[*state]).collect::<Vec<_>>();
+
+    while let Some(state) = pending.pop() {
+        for to in &successors[state] {
+            if !is_reachable[*to] {
+                is_reachable[*to] = true;
+                pending.push(*to);
             }
         }
+    }
 
-        counts_per_timer = temp;
+    (0..is_reachable.len()).filter(|state| is_reachable[*state]).collect()
+}
+
+// One day as a linear map on the given states: new_counts = transition * counts
+fn transition_matrix<A: Arithmetic>(model: &LifecycleModel, states: &[usize], arithmetic: &A) -> Matrix<A::Value> {
+    let mut index = vec![None; model.num_states()];
+    let mut entries = vec![0u64; states.len() * states.len()];
+
+    for (i, state) in states.iter().enumerate() {
+        index[*state] = Some(i);
+    }
+
+    for (from, to, multiplicity) in model.transitions() {
+        if let (Some(from), Some(to)) = (index[from], index[to]) {
+            entries[to * states.len() + from] += multiplicity;
+        }
     }
 
-    let num_entries: u64 = counts_per_timer.iter().sum();
-    num_entries
+    Matrix::from_fn(arithmetic, states.len(), |row, column| entries[row * states.len() + column])
+}
+
+// Logarithmic in num_days, e.g. feasible for 10^12 days in modular arithmetic. The dense matrix
+// costs O(states^3 log num_days) though, so models with many reachable states (a long death age)
+// are simulated day by day instead whenever that is cheaper.
+fn count_entries_fast<A: Arithmetic>(model: &LifecycleModel, population: &Population, num_days: u64, arithmetic: &A) -> A::Value {
+    let states = reachable_states(model, population);
+
+    let matrix_cost = (states.len() as u128).pow(3) * 2 * (u64::BITS - num_days.leading_zeros()) as u128;
+    let simulation_cost = model.transitions().len() as u128 * num_days as u128;
+
+    let counts = if simulation_cost <= matrix_cost {
+        let mut counts = population.counts_per_state.iter().map(|count| arithmetic.constant(*count)).collect::<Vec<_>>();
+
+        for _day in 0..num_days {
+            counts = step(model, &counts, arithmetic);
+        }
+
+        counts
+    } else {
+        let counts = states.iter().map(|state| arithmetic.constant(population.counts_per_state[*state])).collect::<Vec<_>>();
+        transition_matrix(model, &states, arithmetic).pow(num_days, arithmetic).apply(&counts, arithmetic)
+    };
+
+    counts.iter().fold(arithmetic.constant(0), |sum, count| arithmetic.add(&sum, count))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::count_entries;
+    use crate::{count_entries, count_entries_exact, count_entries_fast, count_history, Overflow};
+    use crate::history::{ascii_chart, to_csv};
+    use crate::lifecycle::LifecycleModel;
+    use crate::matrix::{Exact, Modular};
+    use crate::population::Population;
 
     const TEST_DATA: &str = "3,4,3,1,2";
 
     #[test]
     fn test_part1() {
-        assert_eq!(26, count_entries(TEST_DATA, 18));
-        assert_eq!(5934, count_entries(TEST_DATA, 80));
+        let model = LifecycleModel::default();
+        let population = Population::parse(TEST_DATA, &model).unwrap();
+
+        assert_eq!(Ok(26), count_entries(&model, &population, 18));
+        assert_eq!(Ok(5934), count_entries(&model, &population, 80));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(26984457539, count_entries(TEST_DATA, 256));
+        let model = LifecycleModel::default();
+        let population = Population::parse(TEST_DATA, &model).unwrap();
+
+        assert_eq!(Ok(26984457539), count_entries(&model, &population, 256));
+    }
+
+    #[test]
+    fn test_matrix_exponentiation() {
+        let model = LifecycleModel::default();
+        let population = Population::parse(TEST_DATA, &model).unwrap();
+
+        for num_days in [0, 1, 18, 80, 256] {
+            let expected = count_entries(&model, &population, num_days).unwrap();
+
+            assert_eq!(expected.to_string(), count_entries_fast(&model, &population, num_days as u64, &Exact).to_string());
+            assert_eq!(expected % 1_000_000_007, count_entries_fast(&model, &population, num_days as u64, &Modular::new(1_000_000_007).unwrap()));
+        }
+
+        assert_eq!(
+            "379589061144698259131825683795505058481",
+            count_entries_fast(&model, &population, 1000, &Exact).to_string()
+        );
+
+        let modular = Modular::new(998_244_353).unwrap();
+        assert_eq!(31860008, count_entries_fast(&model, &population, 256, &modular));
+        assert_eq!(267489586, count_entries_fast(&model, &population, 1_000_000_000_000, &modular));
+
+        assert!(Modular::new(0).is_none());
+        assert_eq!(0, count_entries_fast(&model, &population, 1_000_000_000_000, &Modular::new(1).unwrap()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_overflow() {
+        let model = LifecycleModel::default();
+        let population = Population::parse(TEST_DATA, &model).unwrap();
+
+        assert_eq!(Err(Overflow { day: 490 }), count_entries(&model, &population, 500));
+
+        let exact = count_entries_exact(&model, &population, 500);
+        assert_eq!(count_entries_fast(&model, &population, 500, &Exact), exact);
+        assert_eq!("45920814377410229085", exact.to_string());
+        assert_eq!("26984457539", count_entries_exact(&model, &population, 256).to_string());
+    }
+
+    #[test]
+    fn test_lifecycle_model() {
+        let model = LifecycleModel::parse("
+            # faster species that lives for 20 days
+            reset_timer = 4
+            newborn_timer = 5
+            maturity_delay = 2
+            death_age = 20
+            spawn_count = 2
+        ").unwrap();
+        let population = Population::parse(TEST_DATA, &model).unwrap();
+
+        assert_eq!(Ok(29), count_entries(&model, &population, 10));
+        assert_eq!(Ok(13028), count_entries(&model, &population, 50));
+        assert_eq!(Ok(25984352), count_entries(&model, &population, 100));
+        assert_eq!(25984352, count_entries_fast(&model, &population, 100, &Modular::new(1_000_000_007).unwrap()));
+        assert_eq!(869997325, count_entries_fast(&model, &population, 1_000_000, &Modular::new(1_000_000_007).unwrap()));
+
+        // 1800 states would make every matrix product cost billions of operations
+        let model = LifecycleModel::parse("death_age = 200").unwrap();
+        let population = Population::parse(TEST_DATA, &model).unwrap();
+        assert_eq!(count_entries_exact(&model, &population, 1000), count_entries_fast(&model, &population, 1000, &Exact));
+
+        assert_eq!(LifecycleModel::default(), LifecycleModel::parse("reset_timer = 6\n").unwrap());
+        assert_eq!(3, LifecycleModel::parse("reset_timer = 6\n\nspawn = 2").unwrap_err().line);
+        assert_eq!(1, LifecycleModel::parse("death_age = 0").unwrap_err().line);
+    }
+
+    #[test]
+    fn test_history() {
+        let model = LifecycleModel::default();
+        let population = Population::parse(TEST_DATA, &model).unwrap();
+        let history = count_history(&model, &population, 18).unwrap();
+
+        assert_eq!(19, history.len());
+        assert_eq!(vec![0, 1, 1, 2, 1, 0, 0, 0, 0], history[0].counts_per_timer);
+        assert_eq!(vec![1, 0, 0, 0, 1, 1, 3, 1, 2], history[4].counts_per_timer);
+        assert_eq!(26, history[18].total);
+
+        let csv = to_csv(&history);
+        let mut rows = csv.lines();
+        assert_eq!(Some("day,total,timer_0,timer_1,timer_2,timer_3,timer_4,timer_5,timer_6,timer_7,timer_8"), rows.next());
+        assert_eq!(Some("0,5,0,1,1,2,1,0,0,0,0"), rows.next());
+        assert_eq!(Some("18,26,3,5,3,2,2,1,5,1,4"), rows.last());
+
+        let chart = ascii_chart(&history, 19, 5, false);
+        assert_eq!(7, chart.lines().count());
+        assert!(chart.lines().next().unwrap().starts_with("26 |"));
+        assert!(chart.lines().next().unwrap().ends_with('#'));
+    }
+
+    #[test]
+    fn test_parse_population() {
+        let model = LifecycleModel::default();
+        let expected = Population::parse(TEST_DATA, &model).unwrap();
+
+        assert_eq!(Ok(expected.clone()), Population::parse("3 4\n3,\t1, 2\n", &model));
+        assert_eq!(Ok(expected), Population::parse("3\n4\n3\n1\n2", &model));
+
+        let errors = Population::parse("3,4,9\n1, x,2", &model).unwrap_err();
+        assert_eq!(2, errors.len());
+        assert_eq!((3, 1, 5), (errors[0].entry, errors[0].line, errors[0].column));
+        assert_eq!((5, 2, 4), (errors[1].entry, errors[1].line, errors[1].column));
+        assert_eq!("x", errors[1].value);
+
+        let model = LifecycleModel::parse("newborn_timer = 9").unwrap();
+        assert!(Population::parse("9,1", &model).is_ok());
+    }
+}
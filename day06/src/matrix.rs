@@ -0,0 +1,148 @@
+use crate::bigint::BigUint;
+
+// The operations the fast simulation needs from a number type
+pub trait Arithmetic {
+    type Value: Clone;
+
+    fn constant(&self, value: u64) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+pub struct Modular {
+    modulus: u64,
+}
+
+impl Modular {
+    // There are no residues modulo 0
+    pub fn new(modulus: u64) -> Option<Self> {
+        (modulus > 0).then_some(Modular { modulus })
+    }
+}
+
+impl Arithmetic for Modular {
+    type Value = u64;
+
+    fn constant(&self, value: u64) -> u64 {
+        value % self.modulus
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.modulus as u128) as u64
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 * *b as u128) % self.modulus as u128) as u64
+    }
+}
+
+// u64 arithmetic where None marks an overflow
+pub struct Checked;
+
+impl Arithmetic for Checked {
+    type Value = Option<u64>;
+
+    fn constant(&self, value: u64) -> Option<u64> {
+        Some(value)
+    }
+
+    fn add(&self, a: &Option<u64>, b: &Option<u64>) -> Option<u64> {
+        a.zip(*b).and_then(|(a, b)| a.checked_add(b))
+    }
+
+    fn mul(&self, a: &Option<u64>, b: &Option<u64>) -> Option<u64> {
+        a.zip(*b).and_then(|(a, b)| a.checked_mul(b))
+    }
+}
+
+pub struct Exact;
+
+impl Arithmetic for Exact {
+    type Value = BigUint;
+
+    fn constant(&self, value: u64) -> BigUint {
+        BigUint::from(value)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a.add(b)
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a.mul(b)
+    }
+}
+
+// Square matrix, row-major
+pub struct Matrix<V> {
+    size: usize,
+    values: Vec<V>,
+}
+
+impl<V: Clone> Matrix<V> {
+    pub fn from_fn<A: Arithmetic<Value = V>>(arithmetic: &A, size: usize, f: impl Fn(usize, usize) -> u64) -> Self {
+        let values = (0..size * size)
+            .map(|i| arithmetic.constant(f(i / size, i % size)))
+            .collect();
+
+        Matrix {
+            size,
+            values,
+        }
+    }
+
+    pub fn identity<A: Arithmetic<Value = V>>(arithmetic: &A, size: usize) -> Self {
+        Matrix::from_fn(arithmetic, size, |row, column| (row == column) as u64)
+    }
+
+    pub fn mul<A: Arithmetic<Value = V>>(&self, other: &Matrix<V>, arithmetic: &A) -> Matrix<V> {
+        let size = self.size;
+        let mut values = Vec::with_capacity(size * size);
+
+        for row in 0..size {
+            for column in 0..size {
+                let value = (0..size).fold(arithmetic.constant(0), |sum, k| {
+                    arithmetic.add(&sum, &arithmetic.mul(&self.values[row * size + k], &other.values[k * size + column]))
+                });
+                values.push(value);
+            }
+        }
+
+        Matrix {
+            size,
+            values,
+        }
+    }
+
+    // Exponentiation by squaring, O(size^3 log exponent)
+    pub fn pow<A: Arithmetic<Value = V>>(&self, exponent: u64, arithmetic: &A) -> Matrix<V> {
+        let mut result = Matrix::identity(arithmetic, self.size);
+        let mut base = Matrix {
+            size: self.size,
+            values: self.values.clone(),
+        };
+        let mut exponent = exponent;
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base, arithmetic);
+            }
+
+            exponent >>= 1;
+
+            if exponent > 0 {
+                base = base.mul(&base, arithmetic);
+            }
+        }
+
+        result
+    }
+
+    pub fn apply<A: Arithmetic<Value = V>>(&self, vector: &[V], arithmetic: &A) -> Vec<V> {
+        (0..self.size)
+            .map(|row| (0..self.size).fold(arithmetic.constant(0), |sum, k| {
+                arithmetic.add(&sum, &arithmetic.mul(&self.values[row * self.size + k], &vector[k]))
+            }))
+            .collect()
+    }
+}
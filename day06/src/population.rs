@@ -0,0 +1,77 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::lifecycle::LifecycleModel;
+
+#[derive(Debug, PartialEq)]
+pub struct TimerError {
+    // 1-based index of the entry and its location in the input
+    pub entry: usize,
+    pub line: usize,
+    pub column: usize,
+    pub value: String,
+    pub reason: String,
+}
+
+impl fmt::Display for TimerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "entry {} ('{}') at {}:{}: {}", self.entry, self.value, self.line, self.column, self.reason)
+    }
+}
+
+// Number of fish per lifecycle state at the start of the simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Population {
+    pub counts_per_state: Vec<u64>,
+}
+
+impl Population {
+    // Timers may be separated by commas, whitespace or newlines; all invalid entries are reported
+    pub fn parse(input: &str, model: &LifecycleModel) -> Result<Self, Vec<TimerError>> {
+        let mut counts_per_state = vec![0u64; model.num_states()];
+        let mut errors = vec![];
+
+        for (i, (line, column, value)) in tokenize(input).into_iter().enumerate() {
+            let error = |reason: String| TimerError { entry: i + 1, line, column, value: value.to_string(), reason };
+
+            match value.parse::<usize>() {
+                Ok(timer) if timer <= model.max_timer() => counts_per_state[model.initial_state(timer)] += 1,
+                Ok(_) => errors.push(error(format!("timer exceeds maximum of {}", model.max_timer()))),
+                Err(_) => errors.push(error("not a timer value".to_string())),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Population {
+                counts_per_state,
+            })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// (line, column, entry), both 1-based
+fn tokenize(input: &str) -> Vec<(usize, usize, &str)> {
+    let mut tokens = vec![];
+
+    for (line_index, line) in input.lines().enumerate() {
+        let mut start = None;
+
+        for (offset, char) in line.char_indices().chain(std::iter::once((line.len(), ','))) {
+            let is_separator = char == ',' || char.is_whitespace();
+
+            match (start, is_separator) {
+                (None, false) => start = Some(offset),
+                (Some(begin), true) => {
+                    let column = line[..begin].chars().count() + 1;
+                    tokens.push((line_index + 1, column, &line[begin..offset]));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    tokens
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::fuel::FuelCost;
+
+pub struct CostCurve {
+    // (target, total fuel) for every integer target between the outermost crabs
+    pub costs: Vec<(i64, i64)>,
+    pub optimum: i64,
+    pub optimal_targets: Vec<i64>,
+}
+
+impl CostCurve {
+    pub fn new<C: FuelCost + ?Sized>(numbers: &[i64], cost: &C) -> Option<Self> {
+        // crabs sharing a position cost the same, so only distinct positions need evaluating
+        let mut crabs_per_position: BTreeMap<i64, i64> = BTreeMap::new();
+        for number in numbers {
+            *crabs_per_position.entry(*number).or_insert(0) += 1;
+        }
+
+        let (min, max) = (*crabs_per_position.keys().next()?, *crabs_per_position.keys().last()?);
+
+        let costs = (min..=max)
+            .map(|target| {
+                let fuel = crabs_per_position.iter()
+                    .map(|(position, count)| count * cost.cost((position - target).abs()))
+                    .sum();
+                (target, fuel)
+            })
+            .collect::<Vec<_>>();
+
+        let optimum = costs.iter().map(|(_, fuel)| *fuel).min()?;
+        let optimal_targets = costs.iter()
+            .filter(|(_, fuel)| *fuel == optimum)
+            .map(|(target, _)| *target)
+            .collect();
+
+        Some(CostCurve {
+            costs,
+            optimum,
+            optimal_targets,
+        })
+    }
+
+    // Targets costing at most `tolerance` more than the optimum, i.e. how flat the curve is around it
+    pub fn targets_within(&self, tolerance: i64) -> Vec<i64> {
+        self.costs.iter()
+            .filter(|(_, fuel)| *fuel <= self.optimum + tolerance)
+            .map(|(target, _)| *target)
+            .collect()
+    }
+}
+
+impl fmt::Display for CostCurve {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = self.costs.iter()
+            .map(|(target, fuel)| {
+                let marker = if *fuel == self.optimum { " *" } else { "" };
+                format!("{},{}{}", target, fuel, marker)
+            })
+            .collect::<Vec<String>>();
+
+        let optimal_targets = self.optimal_targets.iter().map(|target| target.to_string()).collect::<Vec<_>>();
+
+        write!(
+            f, "target,fuel\n{}\noptimum: {} at {}",
+            lines.join("\n"),
+            self.optimum,
+            optimal_targets.join(", ")
+        )
+    }
+}
@@ -0,0 +1,148 @@
+pub trait FuelCost {
+    fn cost(&self, steps: i64) -> i64;
+
+    // Convex and non-decreasing in the number of steps, which makes the total cost convex in the target
+    fn is_convex(&self) -> bool {
+        false
+    }
+
+    // Targets known to contain an optimum, if there is a closed form for this cost model
+    fn candidates(&self, _sorted_numbers: &[i64]) -> Option<Vec<i64>> {
+        None
+    }
+
+    // (linear, quadratic, divisor) if cost(steps) == (linear * steps + quadratic * steps^2) / divisor,
+    // which allows summing over many crabs with prefix sums
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        None
+    }
+}
+
+pub struct Linear;
+
+impl FuelCost for Linear {
+    fn cost(&self, steps: i64) -> i64 {
+        steps
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    // any median minimises the sum of absolute distances, the lower one is the smallest optimal target
+    fn candidates(&self, sorted_numbers: &[i64]) -> Option<Vec<i64>> {
+        Some(vec![sorted_numbers[(sorted_numbers.len() - 1) / 2]])
+    }
+
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        Some((1, 0, 1))
+    }
+}
+
+pub struct Triangular;
+
+impl FuelCost for Triangular {
+    fn cost(&self, steps: i64) -> i64 {
+        // (1..steps + 1).sum::<i64>()
+
+        // better (https://math.stackexchange.com/questions/1100897/sum-of-consecutive-numbers)
+        (steps * (steps + 1)) / 2
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    // The derivative of the total is n * (x - mean) plus at most n/2 in either direction,
+    // so the integer optimum lies within one step of the mean
+    fn candidates(&self, sorted_numbers: &[i64]) -> Option<Vec<i64>> {
+        let sum: i64 = sorted_numbers.iter().sum();
+        let mean = sum.div_euclid(sorted_numbers.len() as i64);
+
+        Some((mean - 1..=mean + 2).collect())
+    }
+
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        Some((1, 1, 2))
+    }
+}
+
+pub struct Quadratic;
+
+impl FuelCost for Quadratic {
+    fn cost(&self, steps: i64) -> i64 {
+        steps * steps
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        Some((0, 1, 1))
+    }
+}
+
+// Engines that never burn more than `max_fuel`, no matter how far they go
+pub struct Capped<C: FuelCost> {
+    pub inner: C,
+    pub max_fuel: i64,
+}
+
+impl<C: FuelCost> FuelCost for Capped<C> {
+    fn cost(&self, steps: i64) -> i64 {
+        self.inner.cost(steps).min(self.max_fuel)
+    }
+}
+
+pub struct Custom<F: Fn(i64) -> i64> {
+    pub cost: F,
+    pub is_convex: bool,
+}
+
+impl<F: Fn(i64) -> i64> FuelCost for Custom<F> {
+    fn cost(&self, steps: i64) -> i64 {
+        (self.cost)(steps)
+    }
+
+    fn is_convex(&self) -> bool {
+        self.is_convex
+    }
+}
+
+pub fn total_cost<C: FuelCost + ?Sized>(numbers: &[i64], target: i64, cost: &C) -> i64 {
+    numbers.iter().map(|number| cost.cost((number - target).abs())).sum()
+}
+
+// (target, fuel) with the lowest fuel, preferring the smallest target on ties
+pub fn find_optimal_moves<C: FuelCost + ?Sized>(numbers: &[i64], cost: &C) -> Option<(i64, i64)> {
+    let mut numbers = numbers.to_vec();
+    numbers.sort_unstable();
+
+    let (min, max) = (*numbers.first()?, *numbers.last()?);
+    let evaluate = |target: i64| (target, total_cost(&numbers, target, cost));
+    let best = |a: (i64, i64), b: (i64, i64)| if (b.1, b.0) < (a.1, a.0) { b } else { a };
+
+    if let Some(candidates) = cost.candidates(&numbers) {
+        return candidates.into_iter().map(evaluate).reduce(best);
+    }
+
+    if !cost.is_convex() {
+        return (min..=max).map(evaluate).reduce(best);
+    }
+
+    // binary search for the first target where the total stops decreasing
+    let (mut low, mut high) = (min, max);
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        if total_cost(&numbers, middle, cost) <= total_cost(&numbers, middle + 1, cost) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    Some(evaluate(low))
+}
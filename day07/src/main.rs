@@ -1,76 +1,189 @@
+mod curve;
+mod fuel;
+mod rendezvous;
+
+use curve::CostCurve;
+use fuel::{find_optimal_moves, Capped, Custom, FuelCost, Linear, Quadratic, Triangular};
+use rendezvous::find_rendezvous;
+
 fn main() {
     let input = include_str!("../resources/input");
     let numbers = parse_input(input);
+    let args: Vec<String> = std::env::args().collect();
 
-    let moves = find_optimal_moves_1(&numbers);
-    println!("[1/2] Result: {}", moves);
+    // curve [model] [tolerance]: fuel for every target, with the optimum and near-optimal targets
+    if args.get(1).map(String::as_str) == Some("curve") {
+        let model = cost_model(args.get(2).map_or("triangular", String::as_str));
+        let curve = CostCurve::new(&numbers, model.as_ref()).unwrap();
+        println!("{}", curve);
 
-    let moves = find_optimal_moves_2(&numbers);
-    println!("[2/2] Result: {}", moves);
-}
+        if let Some(tolerance) = args.get(3).map(|v| v.parse::<i64>().unwrap()) {
+            let targets = curve.targets_within(tolerance);
+            println!("within {}: {}..={}", tolerance, targets.first().unwrap(), targets.last().unwrap());
+        }
+        return;
+    }
 
-fn parse_input(input: &str) -> Vec<i64> {
-    input.trim().split(',').map(|number| number.parse::<i64>().unwrap()).collect()
-}
+    // rendezvous <k> [model]: best k meeting points and the fuel saved compared to a single one
+    if args.get(1).map(String::as_str) == Some("rendezvous") {
+        let num_points = args.get(2).map_or(2, |v| v.parse::<usize>().unwrap());
+        let model = cost_model(args.get(3).map_or("triangular", String::as_str));
 
-fn find_optimal_moves_1(numbers: &Vec<i64>) -> i64 {
-    let mut best_moves: i64 = i64::MAX;
+        let (_, single) = find_optimal_moves(&numbers, model.as_ref()).unwrap();
+        let rendezvous = find_rendezvous(&numbers, num_points, model.as_ref()).unwrap();
 
-    for current in numbers {
-        let moves = numbers.iter().map(|number| (number - current).abs()).sum();
-        if moves < best_moves {
-            best_moves = moves;
+        for (i, point) in rendezvous.meeting_points.iter().enumerate() {
+            let num_crabs = rendezvous.assignment.iter().filter(|assigned| **assigned == i).count();
+            println!("Meeting point {}: {} crab(s)", point, num_crabs);
         }
+
+        println!("Result: {} (saves {})", rendezvous.fuel, single - rendezvous.fuel);
+        return;
     }
 
-    best_moves
-    // This can be sped up by using the median:
-    // let mut numbers = numbers;
-    // numbers.sort();
-    // let median = numbers[numbers.len() / 2];
-    // let moves = numbers.iter().map(|number| (number - median).abs()).sum();
-    // moves
-}
+    let (_, moves) = find_optimal_moves(&numbers, &Linear).unwrap();
+    println!("[1/2] Result: {}", moves);
 
-fn find_optimal_moves_2(numbers: &Vec<i64>) -> i64 {
-    let mut best_moves: i64 = i64::MAX;
+    let (_, moves) = find_optimal_moves(&numbers, &Triangular).unwrap();
+    println!("[2/2] Result: {}", moves);
 
-    for current in numbers {
-        let moves = numbers.iter()
-            .map(|number| {
-                let steps: i64 = (number - current).abs();
-                // (1..steps + 1).sum::<i64>()
+    // other engine models, selected by name
+    if let Some(name) = args.get(1) {
+        let (target, moves) = find_optimal_moves(&numbers, cost_model(name).as_ref()).unwrap();
+        println!("[{}] Result: {} (position {})", name, moves, target);
+    }
+}
 
-                // better (https://math.stackexchange.com/questions/1100897/sum-of-consecutive-numbers)
-                (steps * (steps + 1)) / 2
-            }
-            ).sum();
-        if moves < best_moves {
-            best_moves = moves;
-        }
+fn cost_model(name: &str) -> Box<dyn FuelCost> {
+    match name {
+        "linear" => Box::new(Linear),
+        "triangular" => Box::new(Triangular),
+        "quadratic" => Box::new(Quadratic),
+        "capped" => Box::new(Capped { inner: Triangular, max_fuel: 1000 }),
+        "cubic" => Box::new(Custom { cost: |steps: i64| steps * steps * steps, is_convex: true }),
+        _ => panic!("Unknown cost model {}", name),
     }
+}
 
-    best_moves
+fn parse_input(input: &str) -> Vec<i64> {
+    input.trim().split(',').map(|number| number.parse::<i64>().unwrap()).collect()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fuel::{total_cost, FuelCost};
 
     const TEST_DATA: &str = "16,1,2,0,4,2,7,1,2,14";
 
     #[test]
     fn test_part1() {
         let numbers = parse_input(TEST_DATA);
-        let moves = find_optimal_moves_1(&numbers);
+        let (target, moves) = find_optimal_moves(&numbers, &Linear).unwrap();
 
+        assert_eq!(2, target);
         assert_eq!(37, moves);
     }
 
     #[test]
     fn test_part2() {
         let numbers = parse_input(TEST_DATA);
-        let moves = find_optimal_moves_2(&numbers);
-        assert_eq!(170, moves);
+        let (target, moves) = find_optimal_moves(&numbers, &Triangular).unwrap();
+        assert_eq!(5, target);
+        assert_eq!(168, moves);
     }
-}
\ No newline at end of file
+
+    // (smallest optimal target, fuel)
+    fn find_optimal_moves_brute_force<C: FuelCost + ?Sized>(numbers: &[i64], cost: &C) -> (i64, i64) {
+        let (min, max) = (*numbers.iter().min().unwrap(), *numbers.iter().max().unwrap());
+        (min..=max).map(|target| (total_cost(numbers, target, cost), target)).min().map(|(fuel, target)| (target, fuel)).unwrap()
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut state: u64 = 7;
+        let mut next = |bound: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 33) % bound) as i64
+        };
+
+        let capped = Capped { inner: Quadratic, max_fuel: 400 };
+        let convex_custom = Custom { cost: |steps: i64| steps * steps * steps, is_convex: true };
+        let bumpy_custom = Custom { cost: |steps: i64| steps % 7, is_convex: false };
+
+        for size in 1..60 {
+            let spread = 1 + next(500) as u64;
+            let numbers = (0..size).map(|_| next(spread) - 100).collect::<Vec<_>>();
+
+            let models: [&dyn FuelCost; 6] = [&Linear, &Triangular, &Quadratic, &capped, &convex_custom, &bumpy_custom];
+
+            for model in models {
+                assert_eq!(find_optimal_moves_brute_force(&numbers, model), find_optimal_moves(&numbers, model).unwrap());
+            }
+        }
+
+        assert_eq!(None, find_optimal_moves(&[], &Linear));
+    }
+
+    #[test]
+    fn test_cost_curve() {
+        let numbers = parse_input(TEST_DATA);
+        let curve = CostCurve::new(&numbers, &Triangular).unwrap();
+
+        assert_eq!(17, curve.costs.len());
+        assert_eq!((0, 290), curve.costs[0]);
+        assert_eq!((2, 206), curve.costs[2]);
+        assert_eq!(168, curve.optimum);
+        assert_eq!(vec![5], curve.optimal_targets);
+        assert_eq!(vec![4, 5, 6], curve.targets_within(10));
+
+        let curve = CostCurve::new(&[1, 2, 3, 4], &Linear).unwrap();
+        assert_eq!(vec![2, 3], curve.optimal_targets);
+        assert_eq!(Some((2, 4)), find_optimal_moves(&[4, 3, 2, 1], &Linear));
+        assert!(curve.to_string().ends_with("optimum: 4 at 2, 3"));
+    }
+
+    // cheapest assignment of every crab to one of the given meeting points
+    fn fuel_for<C: FuelCost + ?Sized>(numbers: &[i64], points: &[i64], cost: &C) -> i64 {
+        numbers.iter()
+            .map(|number| points.iter().map(|point| cost.cost((number - point).abs())).min().unwrap())
+            .sum()
+    }
+
+    #[test]
+    fn test_rendezvous() {
+        let numbers = parse_input(TEST_DATA);
+
+        for model in [&Linear as &dyn FuelCost, &Triangular, &Quadratic] {
+            let single = find_rendezvous(&numbers, 1, model).unwrap();
+            assert_eq!(find_optimal_moves(&numbers, model).unwrap().1, single.fuel);
+
+            // exhaustive search over all pairs of meeting points
+            let pair = find_rendezvous(&numbers, 2, model).unwrap();
+            let expected = (0..=16)
+                .flat_map(|a| (a..=16).map(move |b| (a, b)))
+                .map(|(a, b)| fuel_for(&numbers, &[a, b], model))
+                .min()
+                .unwrap();
+
+            assert_eq!(expected, pair.fuel);
+            assert_eq!(fuel_for(&numbers, &pair.meeting_points, model), pair.fuel);
+            assert_eq!(numbers.len(), pair.assignment.len());
+        }
+
+        let rendezvous = find_rendezvous(&numbers, 3, &Linear).unwrap();
+        assert_eq!(vec![2, 7, 14], rendezvous.meeting_points);
+        assert_eq!(8, rendezvous.fuel);
+        assert_eq!(vec![2, 0, 0, 0, 0, 0, 1, 0, 0, 2], rendezvous.assignment);
+
+        assert_eq!(0, find_rendezvous(&numbers, 20, &Triangular).unwrap().fuel);
+
+        // the divide and conquer layers for convex costs agree with the full dynamic programme
+        let unflagged = Custom { cost: |steps: i64| steps * (steps + 1) / 2, is_convex: false };
+        let numbers = (0..80).map(|i| (i * i * 37) % 101).collect::<Vec<_>>();
+
+        for num_points in 1..6 {
+            assert_eq!(find_rendezvous(&numbers, num_points, &unflagged).unwrap().fuel, find_rendezvous(&numbers, num_points, &Triangular).unwrap().fuel);
+        }
+    }
+}
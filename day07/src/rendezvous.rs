@@ -0,0 +1,199 @@
+use crate::fuel::FuelCost;
+
+pub struct Rendezvous {
+    pub meeting_points: Vec<i64>,
+    pub fuel: i64,
+    // index into meeting_points for every crab, in input order
+    pub assignment: Vec<usize>,
+}
+
+// Distinct crab positions with prefix sums of count, position and position^2
+struct Positions {
+    positions: Vec<i64>,
+    counts: Vec<i64>,
+    prefix: Vec<(i128, i128, i128)>,
+}
+
+impl Positions {
+    fn new(numbers: &[i64]) -> Self {
+        let mut sorted = numbers.to_vec();
+        sorted.sort_unstable();
+
+        let mut positions: Vec<i64> = vec![];
+        let mut counts: Vec<i64> = vec![];
+
+        for number in sorted {
+            if positions.last() == Some(&number) {
+                *counts.last_mut().unwrap() += 1;
+            } else {
+                positions.push(number);
+                counts.push(1);
+            }
+        }
+
+        let mut prefix = vec![(0i128, 0i128, 0i128)];
+
+        for (position, count) in positions.iter().zip(&counts) {
+            let (c, p, q) = *prefix.last().unwrap();
+            let (position, count) = (*position as i128, *count as i128);
+            prefix.push((c + count, p + count * position, q + count * position * position));
+        }
+
+        Positions {
+            positions,
+            counts,
+            prefix,
+        }
+    }
+
+    fn sums(&self, from: usize, to: usize) -> (i128, i128, i128) {
+        let (c1, p1, q1) = self.prefix[from];
+        let (c2, p2, q2) = self.prefix[to];
+        (c2 - c1, p2 - p1, q2 - q1)
+    }
+
+    // Fuel for the crabs at positions[from..to] to meet at target
+    fn cost_at<C: FuelCost + ?Sized>(&self, from: usize, to: usize, target: i64, cost: &C) -> i64 {
+        match cost.polynomial() {
+            Some((linear, quadratic, divisor)) => {
+                let split = from + self.positions[from..to].partition_point(|position| *position < target);
+                let t = target as i128;
+
+                let (left_count, left_sum, left_squares) = self.sums(from, split);
+                let (right_count, right_sum, right_squares) = self.sums(split, to);
+
+                let distances = (t * left_count - left_sum) + (right_sum - t * right_count);
+                let squares = (left_squares + right_squares) - 2 * t * (left_sum + right_sum) + t * t * (left_count + right_count);
+
+                ((linear as i128 * distances + quadratic as i128 * squares) / divisor as i128) as i64
+            }
+            None => (from..to)
+                .map(|i| self.counts[i] * cost.cost((self.positions[i] - target).abs()))
+                .sum(),
+        }
+    }
+
+    fn best_target<C: FuelCost + ?Sized>(&self, from: usize, to: usize, cost: &C) -> (i64, i64) {
+        let (mut low, mut high) = (self.positions[from], self.positions[to - 1]);
+
+        if !cost.is_convex() {
+            return (low..=high)
+                .map(|target| (target, self.cost_at(from, to, target, cost)))
+                .min_by_key(|(target, fuel)| (*fuel, *target))
+                .unwrap();
+        }
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+
+            if self.cost_at(from, to, middle, cost) <= self.cost_at(from, to, middle + 1, cost) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+
+        (low, self.cost_at(from, to, low, cost))
+    }
+}
+
+// With a convex cost every crab goes to its nearest meeting point, so the groups are contiguous in
+// sorted order and the dynamic programme over split points is exact. For other costs the best
+// contiguous split is refined by sending each crab to its cheapest meeting point.
+pub fn find_rendezvous<C: FuelCost + ?Sized>(numbers: &[i64], num_points: usize, cost: &C) -> Option<Rendezvous> {
+    let positions = Positions::new(numbers);
+    let m = positions.positions.len();
+    let k = num_points.min(m);
+
+    if k == 0 {
+        return None;
+    }
+
+    // (target, fuel) for a single meeting point serving positions[from..to], computed when needed
+    let group = |from: usize, to: usize| positions.best_target(from, to, cost);
+
+    // fuel[j][i]: cheapest way to serve the first i positions with j meeting points
+    let mut fuel = vec![vec![i64::MAX; m + 1]; k + 1];
+    let mut split = vec![vec![0usize; m + 1]; k + 1];
+    fuel[0][0] = 0;
+
+    if cost.is_convex() {
+        // the group costs are then Monge, so the best split point never decreases with i
+        for j in 1..=k {
+            let (previous, current) = fuel.split_at_mut(j);
+            solve_layer(&previous[j - 1], &mut current[0], &mut split[j], (j, m), (j - 1, m - 1), &|from, to| group(from, to).1);
+        }
+    } else {
+        // one column of group costs at a time, each is shared by all layers
+        for i in 1..=m {
+            let column = (0..i).map(|from| group(from, i).1).collect::<Vec<_>>();
+
+            for j in 1..=k.min(i) {
+                for from in j - 1..i {
+                    if fuel[j - 1][from] == i64::MAX {
+                        continue;
+                    }
+
+                    let total = fuel[j - 1][from] + column[from];
+
+                    if total < fuel[j][i] {
+                        fuel[j][i] = total;
+                        split[j][i] = from;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut meeting_points = vec![];
+    let mut to = m;
+
+    for j in (1..=k).rev() {
+        let from = split[j][to];
+        meeting_points.push(group(from, to).0);
+        to = from;
+    }
+
+    meeting_points.reverse();
+
+    let assignment = numbers.iter()
+        .map(|number| {
+            (0..meeting_points.len())
+                .min_by_key(|i| cost.cost((number - meeting_points[*i]).abs()))
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let fuel = numbers.iter()
+        .zip(&assignment)
+        .map(|(number, i)| cost.cost((number - meeting_points[*i]).abs()))
+        .sum();
+
+    Some(Rendezvous {
+        meeting_points,
+        fuel,
+        assignment,
+    })
+}
+
+// Fills fuel[low..=high] of one layer, knowing that their split points lie within first..=last
+fn solve_layer(previous: &[i64], fuel: &mut [i64], split: &mut [usize], (low, high): (usize, usize), (first, last): (usize, usize), group: &impl Fn(usize, usize) -> i64) {
+    if low > high {
+        return;
+    }
+
+    let middle = low + (high - low) / 2;
+    let best = (first..=last.min(middle - 1))
+        .filter(|from| previous[*from] != i64::MAX)
+        .map(|from| (previous[from] + group(from, middle), from))
+        .min()
+        .unwrap_or((i64::MAX, first));
+
+    (fuel[middle], split[middle]) = best;
+
+    if middle > low {
+        solve_layer(previous, fuel, split, (low, middle - 1), (first, best.1), group);
+    }
+
+    solve_layer(previous, fuel, split, (middle + 1, high), (best.1, last), group);
+}
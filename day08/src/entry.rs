@@ -0,0 +1,67 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::glyphs::GlyphTable;
+use crate::solver::Mask;
+
+#[derive(Debug, PartialEq)]
+pub struct EntryError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for EntryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+// One note line: the observed patterns before the '|', the output patterns after it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    // line number in the notes, counting from 1
+    pub line: usize,
+    pub patterns: Vec<Mask>,
+    pub output: Vec<Mask>,
+    // patterns using a wire the display doesn't have, as written
+    pub unknown: Vec<String>,
+}
+
+impl Entry {
+    pub fn parse(table: &GlyphTable, number: usize, line: &str) -> Result<Self, String> {
+        let (patterns, output) = line.split_once('|')
+            .ok_or_else(|| "missing '|' between patterns and output".to_string())?;
+
+        let mut unknown = vec![];
+        let mut parse_all = |patterns: &str| patterns.split_ascii_whitespace()
+            .filter_map(|pattern| {
+                let mask = table.parse_pattern(pattern);
+
+                if mask.is_none() {
+                    unknown.push(pattern.to_string());
+                }
+
+                mask
+            })
+            .collect::<Vec<_>>();
+
+        let (patterns, output) = (parse_all(patterns), parse_all(output));
+
+        Ok(Entry {
+            line: number,
+            patterns,
+            output,
+            unknown,
+        })
+    }
+}
+
+// One result per non-blank line, so a malformed line doesn't stop the others from being read.
+// Line numbers count from the first line of the input.
+pub fn parse_entries(table: &GlyphTable, input: &str) -> Vec<Result<Entry, EntryError>> {
+    input.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| Entry::parse(table, i + 1, line).map_err(|reason| EntryError { line: i + 1, reason }))
+        .collect()
+}
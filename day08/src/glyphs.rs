@@ -0,0 +1,136 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::solver::Mask;
+
+#[derive(Debug, PartialEq)]
+pub struct TableError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+// Segment names double as wire names, segment i is bit i of a mask
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphTable {
+    pub segments: Vec<char>,
+    pub glyphs: Vec<(char, Mask)>,
+}
+
+impl GlyphTable {
+    pub fn seven_segment() -> Self {
+        GlyphTable::parse(include_str!("../resources/seven_segment")).unwrap()
+    }
+
+    pub fn fourteen_segment() -> Self {
+        GlyphTable::parse(include_str!("../resources/fourteen_segment")).unwrap()
+    }
+
+    pub fn sixteen_segment() -> Self {
+        GlyphTable::parse(include_str!("../resources/sixteen_segment")).unwrap()
+    }
+
+    // `segments = <names>` first, then `<symbol> = <lit segments>` per line, `#` starts a comment
+    pub fn parse(definition: &str) -> Result<Self, TableError> {
+        let mut segments: Option<Vec<char>> = None;
+        let mut glyphs: Vec<(char, Mask)> = vec![];
+
+        for (i, line) in definition.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |reason: String| TableError { line: i + 1, reason };
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| error(format!("expected 'symbol = segments', found '{}'", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let names = match &segments {
+                None if key == "segments" => {
+                    let names = value.chars().collect::<Vec<_>>();
+
+                    if names.is_empty() || names.len() > Mask::BITS as usize {
+                        return Err(error(format!("a display needs 1 to {} segments", Mask::BITS)));
+                    }
+
+                    if let Some(name) = names.iter().enumerate().find(|(j, name)| names[..*j].contains(name)).map(|(_, name)| name) {
+                        return Err(error(format!("segment '{}' is named twice", name)));
+                    }
+
+                    segments = Some(names);
+                    continue;
+                }
+                None => return Err(error("the segment names have to come first".to_string())),
+                Some(names) => names,
+            };
+
+            let mut chars = key.chars();
+            let symbol = match (chars.next(), chars.next()) {
+                (Some(symbol), None) => symbol,
+                _ => return Err(error(format!("symbol '{}' is not a single character", key))),
+            };
+
+            let mask = value.chars().try_fold(0 as Mask, |mask, name| {
+                match names.iter().position(|segment| *segment == name) {
+                    Some(segment) => Ok(mask | 1 << segment),
+                    None => Err(error(format!("unknown segment '{}' in symbol '{}'", name, symbol))),
+                }
+            })?;
+
+            if let Some((other, _)) = glyphs.iter().find(|(other, other_mask)| *other == symbol || *other_mask == mask) {
+                return Err(error(format!("symbol '{}' is indistinguishable from '{}'", symbol, other)));
+            }
+
+            glyphs.push((symbol, mask));
+        }
+
+        match segments {
+            Some(segments) => Ok(GlyphTable { segments, glyphs }),
+            None => Err(TableError { line: definition.lines().count(), reason: "no segment names".to_string() }),
+        }
+    }
+
+    pub fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn all_segments(&self) -> Mask {
+        Mask::MAX >> (Mask::BITS as usize - self.num_segments())
+    }
+
+    pub fn glyphs_with_size(&self, size: u32) -> impl Iterator<Item=Mask> + '_ {
+        self.glyphs.iter().map(|(_, mask)| *mask).filter(move |mask| mask.count_ones() == size)
+    }
+
+    // Pattern sizes that identify a symbol on their own, like 1, 4, 7 and 8 on seven segments
+    pub fn unique_sizes(&self) -> Vec<usize> {
+        (0..=self.num_segments() as u32)
+            .filter(|size| self.glyphs_with_size(*size).count() == 1)
+            .map(|size| size as usize)
+            .collect()
+    }
+
+    // None if the pattern uses a wire the display doesn't have
+    pub fn parse_pattern(&self, pattern: &str) -> Option<Mask> {
+        pattern.chars().try_fold(0 as Mask, |mask, name| {
+            self.segments.iter().position(|segment| *segment == name).map(|wire| mask | 1 << wire)
+        })
+    }
+
+    // Wire names of the pattern in segment order
+    pub fn format_pattern(&self, pattern: Mask) -> String {
+        self.segments.iter()
+            .enumerate()
+            .filter(|(wire, _)| pattern & (1 << wire) != 0)
+            .map(|(_, name)| name)
+            .collect()
+    }
+}
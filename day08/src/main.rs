@@ -1,122 +1,178 @@
-use std::collections::HashSet;
+mod entry;
+mod glyphs;
+mod number;
+mod solver;
+
+use entry::{parse_entries, Entry};
+use glyphs::GlyphTable;
+use number::Number;
+use solver::{readings, solve, Mapping, Mask, Solution};
 
 fn main() {
-    let input = include_str!("../resources/input");
-    let mappings = parse_string_mapping(input);
-    let num_unique_outputs = count_unique_outputs(&mappings);
+    let args = std::env::args().collect::<Vec<_>>();
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1));
+
+    // --display seven|fourteen|sixteen|<path> decodes against another glyph table
+    let table = match option("--display") {
+        Some(name) => match load_table(name) {
+            Ok(table) => table,
+            Err(error) => {
+                eprintln!("Invalid display definition {}: {}", name, error);
+                std::process::exit(1);
+            }
+        },
+        None => GlyphTable::seven_segment(),
+    };
+
+    // --input <path> reads the notes from a file instead of the puzzle input
+    let input = match option("--input") {
+        Some(path) => std::fs::read_to_string(path).unwrap_or_else(|error| {
+            eprintln!("Failed to read {}: {}", path, error);
+            std::process::exit(1);
+        }),
+        None => include_str!("../resources/input").to_string(),
+    };
+
+    let (entries, errors): (Vec<_>, Vec<_>) = parse_entries(&table, &input).into_iter().partition(Result::is_ok);
+    let entries = entries.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+
+    let num_unique_outputs = count_unique_outputs(&table, &entries);
     println!("[1/2] Result: {}", num_unique_outputs);
 
-    let mappings = parse_string_mapping(input);
-    let result: usize = mappings.iter().map(solve_mapping).sum();
+    let decodings = entries.iter().map(|entry| decode_entry(&table, entry)).collect::<Vec<_>>();
+    let result = decodings.iter().filter_map(Decoding::value).fold(Number::Small(0), |sum, value| sum.add(&value));
     println!("[2/2] Result: {}", result);
-}
 
-type StringMapping = (Vec<&'static str>, Vec<&'static str>);
+    // --raw prints every reading as shown, including leading zeros
+    let raw = args.iter().any(|arg| arg == "--raw");
 
-fn parse_string_mapping(input: &'static str) -> Vec<StringMapping> {
-    let entries =
-        input.trim()
-            .split('\n').map(|line| {
-            let mut parts = line.split('|');
-            (
-                parts.next().unwrap().trim().split_ascii_whitespace().collect(),
-                parts.next().unwrap().trim().split_ascii_whitespace().collect()
-            )
-        }).collect::<Vec<StringMapping>>();
+    for (entry, decoding) in entries.iter().zip(&decodings) {
+        match decoding {
+            Decoding::Solved(reading) if raw || decoding.value().is_none() => println!("Line {}: {}", entry.line, reading),
+            Decoding::Solved(_) => {}
+            Decoding::Ambiguous(_) => {
+                let mut readings = readings(&table, &entry.output, &entry.patterns, usize::MAX);
+                readings.sort_unstable();
+                println!("Line {}: ambiguous, could be any of {:?}", entry.line, readings);
+            }
+            Decoding::Inconsistent(patterns) => println!("Line {}: inconsistent patterns {}", entry.line, patterns.join(" ")),
+        }
+    }
 
-    entries
-}
+    // lines that couldn't be read are left out of both results
+    for error in errors.into_iter().map(Result::unwrap_err) {
+        println!("Line {}: skipped, {}", error.line, error.reason);
+    }
 
-fn count_unique_outputs(mappings: &Vec<StringMapping>) -> usize {
-    mappings.into_iter().flat_map(|(_, output)| output).filter(|output| {
-        match output.len()
-        {
-            // digits = 1, 4, 7, 8
-            2 | 4 | 3 | 7 => true,
-            _ => false
+    // --wiring prints the deduced wire to segment mapping of every entry
+    if args.iter().any(|arg| arg == "--wiring") {
+        for entry in &entries {
+            match solve_wiring(&table, entry) {
+                Some(wiring) => println!("{}: {}", entry.line, wiring),
+                None => println!("{}: no unique wiring", entry.line),
+            }
         }
-    }).count()
+    }
 }
 
-fn find_with_num_segments(values: &Vec<&str>, num_segments: usize) -> Vec<HashSet<char>> {
-    let result = values.into_iter().filter(|value| value.len() == num_segments);
-    let c = result.map(|value| value.chars().collect::<HashSet<char>>()).collect::<Vec<_>>();
-    c
+fn load_table(name: &str) -> Result<GlyphTable, String> {
+    match name {
+        "seven" => Ok(GlyphTable::seven_segment()),
+        "fourteen" => Ok(GlyphTable::fourteen_segment()),
+        "sixteen" => Ok(GlyphTable::sixteen_segment()),
+        path => {
+            let definition = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+            GlyphTable::parse(&definition).map_err(|error| error.to_string())
+        }
+    }
 }
 
-fn find_with_num_segments_single(values: &Vec<&str>, num_segments: usize) -> HashSet<char> {
-    let matches = find_with_num_segments(values, num_segments);
-    assert_eq!(1, matches.len());
+fn count_unique_outputs(table: &GlyphTable, entries: &[Entry]) -> usize {
+    // on seven segments these are the digits 1, 4, 7 and 8
+    let unique_sizes = table.unique_sizes();
 
-    let m = matches.first().unwrap();
-    m.clone()
+    entries.iter().flat_map(|entry| &entry.output).filter(|output| {
+        unique_sizes.contains(&(output.count_ones() as usize))
+    }).count()
 }
 
-fn solve_mapping(mapping: &StringMapping) -> usize{
-    let (alphabet, output) = mapping;
-
-    let one = find_with_num_segments_single(&alphabet, 2);
-    let four = find_with_num_segments_single(&alphabet, 4);
-    let seven = find_with_num_segments_single(&alphabet, 3);
-    let eight = find_with_num_segments_single(&alphabet, 7);
-
-    let two_three_five = find_with_num_segments(&alphabet, 5);
-    assert_eq!(3, two_three_five.len());
-
-    let three = two_three_five.iter().find(|chars| {
-        one.is_subset(chars)
-    }).unwrap().clone();
+fn solve_wiring<'a>(table: &'a GlyphTable, entry: &Entry) -> Option<Mapping<'a>> {
+    let lit_wires = entry.patterns.iter().chain(&entry.output).fold(0, |mask, pattern| mask | pattern);
 
-    let two_five = two_three_five.into_iter().filter(|chars| !three.eq(chars)).collect::<Vec<HashSet<_>>>();
-    assert_eq!(2, two_five.len());
-
-    let part_of_four: HashSet<char> = four.difference(&one).map(|v| *v).collect::<HashSet<_>>();
-    assert_eq!(2, part_of_four.len());
+    // two or more wires that never light up could be swapped
+    if lit_wires.count_ones() as usize + 1 < table.num_segments() {
+        return None;
+    }
 
-    let five: HashSet<char> = two_five.iter().find(|chars| part_of_four.is_subset(chars)).unwrap().clone();
-    let two = two_five.into_iter().find(|chars| !five.eq(chars)).unwrap();
+    match solve(table, &entry.output, &entry.patterns) {
+        Solution::Unique(wiring) => Some(wiring),
+        _ => None,
+    }
+}
 
-    let zero_six_nine = find_with_num_segments(&alphabet, 6);
-    assert_eq!(3, zero_six_nine.len());
+#[derive(Debug, PartialEq)]
+enum Decoding {
+    Solved(String),
+    // two of the readings some consistent wiring would produce
+    Ambiguous(Vec<String>),
+    // a smallest set of patterns that no wiring can show at the same time, as written
+    Inconsistent(Vec<String>),
+}
 
-    let six = zero_six_nine.iter().find(|chars| {
-        !one.is_subset(chars)
-    }).unwrap().clone();
+impl Decoding {
+    fn value(&self) -> Option<Number> {
+        match self {
+            Decoding::Solved(reading) => Number::parse(reading),
+            _ => None,
+        }
+    }
+}
 
-    let zero_nine = zero_six_nine.into_iter().filter(|chars| !six.eq(chars)).collect::<Vec<HashSet<_>>>();
-    assert_eq!(2, zero_nine.len());
+fn decode_entry(table: &GlyphTable, entry: &Entry) -> Decoding {
+    // a pattern using a wire the display doesn't have conflicts on its own
+    if let Some(pattern) = entry.unknown.first() {
+        return Decoding::Inconsistent(vec![pattern.clone()]);
+    }
 
-    let zero = zero_nine.iter().find(|chars| {
-        !four.is_subset(chars)
-    }).unwrap().clone();
+    // a second reading is enough to tell that the entry can't be decoded
+    let mut readings = readings(table, &entry.output, &entry.patterns, 2);
 
-    let nine = zero_nine.iter().find(|chars| {
-        four.is_subset(chars)
-    }).unwrap().clone();
+    match readings.len() {
+        0 => Decoding::Inconsistent(find_conflict(table, entry.patterns.iter().chain(&entry.output).copied().collect())),
+        1 => Decoding::Solved(readings.remove(0)),
+        _ => {
+            readings.sort_unstable();
+            Decoding::Ambiguous(readings)
+        }
+    }
+}
 
-    let solution = [
-        &zero, &one, &two, &three, &four, &five, &six, &seven, &eight, &nine
-    ];
+// Drops patterns one at a time as long as the rest still has no consistent wiring
+fn find_conflict(table: &GlyphTable, mut conflict: Vec<Mask>) -> Vec<String> {
+    let mut i = 0;
 
-    let result: usize = output.into_iter()
-        .map(|string| string.chars().collect::<HashSet<char>>())
-        .map(
-                |chars|
-                    solution.iter().position(|entry| chars.eq(entry)).unwrap()
-        )
-        .enumerate()
-        .map(|(i, value)|
-            value * 10_usize.pow(3 - i as u32)
-        ).sum();
+    while i < conflict.len() {
+        let mut without = conflict.clone();
+        without.remove(i);
 
+        if solve(table, &[], &without) == Solution::Inconsistent {
+            conflict = without;
+        } else {
+            i += 1;
+        }
+    }
 
-    result
+    conflict.iter().map(|pattern| table.format_pattern(*pattern)).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse_all(table: &GlyphTable, notes: &str) -> Vec<Entry> {
+        parse_entries(table, notes).into_iter().collect::<Result<_, _>>().unwrap()
+    }
+
     const TEST_DATA: &str = "
         be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
         edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
@@ -132,15 +188,139 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let mappings = parse_string_mapping(TEST_DATA);
-        let num_unique_outputs = count_unique_outputs(&mappings);
+        let table = GlyphTable::seven_segment();
+        let entries = parse_all(&table, TEST_DATA);
+        let num_unique_outputs = count_unique_outputs(&table, &entries);
         assert_eq!(26, num_unique_outputs);
     }
 
     #[test]
     fn test_part2() {
-        let mappings = parse_string_mapping(TEST_DATA);
-        let result: usize = mappings.iter().map(solve_mapping).sum();
-        assert_eq!(61229, result);
+        let table = GlyphTable::seven_segment();
+        let entries = parse_all(&table, TEST_DATA);
+        let result = entries.iter()
+            .map(|entry| decode_entry(&table, entry).value().unwrap())
+            .fold(Number::Small(0), |sum, value| sum.add(&value));
+        assert_eq!(Number::Small(61229), result);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_incomplete_patterns() {
+        let table = GlyphTable::seven_segment();
+        let entries = parse_all(&table, "
+            acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf
+            acedgfb cdfbe fbcad dab cefabd eafb ab | cdfeb fcadb cdfeb cdbaf
+            gcdfa dab cdfgeb cagedb | cdfeb fcadb cdfeb cdbaf
+        ");
+
+        let expected = solve_wiring(&table, &entries[0]).unwrap();
+        assert_eq!("a->c b->f c->g d->a e->b f->d g->e", expected.to_string());
+
+        for entry in &entries {
+            assert_eq!(Decoding::Solved("5353".to_string()), decode_entry(&table, entry));
+        }
+
+        assert_eq!(Some(expected), solve_wiring(&table, &entries[1]));
+    }
+
+    #[test]
+    fn test_bad_entries() {
+        let table = GlyphTable::seven_segment();
+        let entries = parse_all(&table, "
+            ab | ab ab dab dab
+            ab abc abcdefg | abcd ab abc abcdefg
+            acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf
+        ");
+
+        assert_eq!(Decoding::Solved("1177".to_string()), decode_entry(&table, &entries[0]));
+        assert_eq!(Decoding::Inconsistent(vec!["abcd".to_string(), "abc".to_string()]), decode_entry(&table, &entries[1]));
+        assert_eq!(Decoding::Solved("5353".to_string()), decode_entry(&table, &entries[2]));
+
+        let ambiguous = parse_all(&table, "cdfeb fcadb | cdfeb fcadb cdfeb cdbaf");
+        match decode_entry(&table, &ambiguous[0]) {
+            Decoding::Ambiguous(readings) => assert!(readings.len() > 1 && readings.iter().all(|r| r[0..1] == r[2..3])),
+            decoding => panic!("Expected ambiguous decoding, got {:?}", decoding),
+        }
+    }
+
+    #[test]
+    fn test_alphanumeric_displays() {
+        let table = GlyphTable::fourteen_segment();
+        let fourteen = parse_all(&table, "fledn ldhjka lhe cie ajdlhefk ejak flkhgae befm ehiclf lbhfaekm jafdek jdgakhe hl kgabj jhlda alfhk kganlh fldake fehld jfaeg gmbn ldjfaeh hkeajd hekjdf clehfid bin fka hblnak abkm kafe ecif fkleah lhkf gklamh kdejlah fjdelka | hkdlaj kjeafd fak fak fleakh djlha kjfhde");
+        assert_eq!(Decoding::Solved("HELLO42".to_string()), decode_entry(&table, &fourteen[0]));
+
+        // the halves of the top and bottom bars are always lit together, so their wiring stays open
+        let table = GlyphTable::sixteen_segment();
+        let sixteen = parse_all(&table, "jcige alhopigfn opfclehag mpjfah pgacfih lo dhbp bdpflaoh goekjl egaipnhf engpoih aefg odlbhfnap adpfhb hgpei ofleag coeglk mljhfoepag jmck pgahef afhepgln laefo ohainefp eoinpglh leonig molceg eioncpgh fnhpkal gmej nlohfpa ipglenhaf hfgoalipne loph agpfolhe bkj olgni | iophgen lhgipeno eolcgk fgipeanh faeg lpho");
+        assert_eq!(Decoding::Solved("PANEL7".to_string()), decode_entry(&table, &sixteen[0]));
+
+        let error = GlyphTable::parse("segments = xyz\n+ = xy\n- = yx\n").unwrap_err();
+        assert_eq!("line 3: symbol '-' is indistinguishable from '+'", error.to_string());
+    }
+
+    #[test]
+    fn test_sparse_entries() {
+        // few patterns leave most of the wiring open, the readings are searched instead of the wirings
+        let table = GlyphTable::sixteen_segment();
+        let entries = parse_all(&table, "abcdefghij | abcdefghij\nabcdefgh | abcdefgh abcdefgh");
+
+        for (entry, size) in entries.iter().zip([10, 8]) {
+            let symbols = table.glyphs.iter().filter(|(_, glyph)| glyph.count_ones() == size).map(|(symbol, _)| *symbol);
+            let mut expected = symbols.map(|symbol| symbol.to_string().repeat(entry.output.len())).collect::<Vec<_>>();
+            let mut found = readings(&table, &entry.output, &entry.patterns, usize::MAX);
+            expected.sort_unstable();
+            found.sort_unstable();
+            assert_eq!(expected, found);
+
+            match decode_entry(&table, entry) {
+                Decoding::Ambiguous(readings) => assert_eq!(2, readings.len()),
+                decoding => panic!("Expected ambiguous decoding, got {:?}", decoding),
+            }
+
+            assert_eq!(None, solve_wiring(&table, entry));
+        }
+    }
+
+    #[test]
+    fn test_parse_entries() {
+        let table = GlyphTable::parse("segments = xyz\n+ = xy\n- = y\n").unwrap();
+
+        // notes read at runtime, not just string literals
+        let notes = ["z yz | yz", "", "yz z | z yz"].join("\n");
+        let entries = parse_all(&table, &notes);
+        assert_eq!(Entry { line: 1, patterns: vec![0b100, 0b110], output: vec![0b110], unknown: vec![] }, entries[0]);
+        assert_eq!(3, entries[1].line);
+        assert_eq!(Decoding::Solved("-+".to_string()), decode_entry(&table, &entries[1]));
+
+        // bad lines are reported on their own while the rest is still decoded
+        let results = parse_entries(&table, &(notes.clone() + "\nz yz | z yz w\nz yz z"));
+        assert_eq!(4, results.len());
+        assert_eq!(Decoding::Inconsistent(vec!["w".to_string()]), decode_entry(&table, results[2].as_ref().unwrap()));
+        assert_eq!("line 5: missing '|' between patterns and output", results[3].as_ref().unwrap_err().to_string());
+        assert_eq!(Decoding::Solved("-+".to_string()), decode_entry(&table, results[1].as_ref().unwrap()));
+
+        let errors = parse_entries(&table, &notes.replace(" | ", " ")).into_iter().map(|result| result.unwrap_err().line).collect::<Vec<_>>();
+        assert_eq!(vec![1, 3], errors);
+    }
+
+    #[test]
+    fn test_long_outputs() {
+        let table = GlyphTable::seven_segment();
+        let patterns = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab";
+        let notes = [
+            format!("{} | cagedb cagedb ab eafb cdfbe cdfgeb", patterns),
+            format!("{} | cagedb cagedb cagedb cagedb cagedb cagedb cagedb cagedb", patterns),
+            format!("{} | {}", patterns, ["cefabd"; 40].join(" ")),
+        ];
+        let entries = parse_all(&table, &notes.join("\n"));
+        let decodings = entries.iter().map(|entry| decode_entry(&table, entry)).collect::<Vec<_>>();
+
+        assert_eq!(Decoding::Solved("001456".to_string()), decodings[0]);
+        assert_eq!(Some(Number::Small(1456)), decodings[0].value());
+        assert_eq!(Some(Number::Small(0)), decodings[1].value());
+
+        let nines = decodings[2].value().unwrap();
+        assert_eq!(Number::Long("9".repeat(40)), nines);
+        assert_eq!(format!("1{}", "0".repeat(40)), nines.add(&Number::Small(1)).to_string());
+    }
+}
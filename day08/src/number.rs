@@ -0,0 +1,64 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+// Decimal reading of a display, kept as digits once it no longer fits into a u128
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Small(u128),
+    Long(String),
+}
+
+impl Number {
+    // None unless the reading consists of digits only, leading zeros are dropped
+    pub fn parse(reading: &str) -> Option<Self> {
+        if reading.is_empty() || !reading.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+
+        Some(match reading.parse::<u128>() {
+            Ok(value) => Number::Small(value),
+            Err(_) => Number::Long(reading.trim_start_matches('0').to_string()),
+        })
+    }
+
+    pub fn add(&self, other: &Number) -> Number {
+        if let (Number::Small(a), Number::Small(b)) = (self, other) {
+            if let Some(sum) = a.checked_add(*b) {
+                return Number::Small(sum);
+            }
+        }
+
+        Number::Long(add_decimal(&self.to_string(), &other.to_string()))
+    }
+}
+
+// Schoolbook addition of two decimal digit strings
+fn add_decimal(a: &str, b: &str) -> String {
+    let mut digits = vec![];
+    let mut a = a.bytes().rev();
+    let mut b = b.bytes().rev();
+    let mut carry = 0;
+
+    loop {
+        let (x, y) = (a.next(), b.next());
+
+        if x.is_none() && y.is_none() && carry == 0 {
+            break;
+        }
+
+        let sum = x.map_or(0, |x| x - b'0') + y.map_or(0, |y| y - b'0') + carry;
+        digits.push(b'0' + sum % 10);
+        carry = sum / 10;
+    }
+
+    digits.iter().rev().map(|digit| *digit as char).collect()
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Small(value) => write!(f, "{}", value),
+            Number::Long(digits) => write!(f, "{}", digits),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::glyphs::GlyphTable;
+
+// One bit per wire or per display segment, wide enough for alphanumeric displays
+pub type Mask = u32;
+
+// Tables can't have more segments than a mask has bits
+pub const MAX_WIRES: usize = Mask::BITS as usize;
+
+// segments[wire] is the display segment the wire is connected to, the names belong to the table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mapping<'a> {
+    segments: [u8; MAX_WIRES],
+    names: &'a [char],
+}
+
+impl fmt::Display for Mapping<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let pairs = self.names.iter()
+            .zip(&self.segments)
+            .map(|(wire, segment)| format!("{}->{}", wire, self.names[*segment as usize]))
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", pairs.join(" "))
+    }
+}
+
+// The wirings of an entry, the search stops as soon as a second one is found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Solution<'a> {
+    Inconsistent,
+    Unique(Mapping<'a>),
+    Ambiguous,
+}
+
+pub fn solve<'a>(table: &'a GlyphTable, output: &[Mask], observed: &[Mask]) -> Solution<'a> {
+    let mut solution = Solution::Inconsistent;
+
+    Search { table, output, observed }.complete(0, Groups::new(table.all_segments()), &mut |groups| {
+        // wires sharing a group can be swapped without changing any symbol
+        solution = match (solution, groups.iter().all(|(wires, _)| wires.count_ones() == 1)) {
+            (Solution::Inconsistent, true) => Solution::Unique(Mapping {
+                segments: groups.wiring(),
+                names: &table.segments,
+            }),
+            _ => Solution::Ambiguous,
+        };
+
+        solution != Solution::Ambiguous
+    });
+
+    solution
+}
+
+// Distinct readings of the output over all consistent wirings, up to max_readings of them.
+// Symbols are chosen for the output patterns first, so a branch ends as soon as its reading is
+// known: either it was found before or a single consistent choice for the rest confirms it.
+pub fn readings(table: &GlyphTable, output: &[Mask], observed: &[Mask], max_readings: usize) -> Vec<String> {
+    let mut readings = vec![];
+    let mut reading = String::new();
+
+    Search { table, output, observed }.read(0, Groups::new(table.all_segments()), &mut reading, &mut readings, max_readings);
+    readings
+}
+
+// Wires and segments partitioned by the glyphs chosen so far: each wire of a group is lit in
+// exactly the patterns whose glyphs light the segments of the group. A wiring exists as long as
+// every group has as many wires as segments, it connects the wires within their groups.
+#[derive(Clone, Copy)]
+struct Groups {
+    groups: [(Mask, Mask); MAX_WIRES],
+    len: usize,
+}
+
+impl Groups {
+    fn new(all_segments: Mask) -> Self {
+        let mut groups = [(0, 0); MAX_WIRES];
+        groups[0] = (all_segments, all_segments);
+
+        Groups { groups, len: 1 }
+    }
+
+    fn iter(&self) -> impl Iterator<Item=&(Mask, Mask)> {
+        self.groups[..self.len].iter()
+    }
+
+    // None if the glyph can't be shown by the pattern under any wiring left
+    fn split(&self, pattern: Mask, glyph: Mask) -> Option<Self> {
+        let mut split = Groups { groups: [(0, 0); MAX_WIRES], len: 0 };
+
+        for (wires, segments) in self.iter() {
+            let lit = (wires & pattern, segments & glyph);
+
+            if lit.0.count_ones() != lit.1.count_ones() {
+                return None;
+            }
+
+            for part in [lit, (wires & !pattern, segments & !glyph)] {
+                if part.0 != 0 {
+                    split.groups[split.len] = part;
+                    split.len += 1;
+                }
+            }
+        }
+
+        Some(split)
+    }
+
+    // Only meaningful once every group holds a single wire
+    fn wiring(&self) -> [u8; MAX_WIRES] {
+        let mut segments = [0; MAX_WIRES];
+
+        for (wires, group_segments) in self.iter() {
+            segments[wires.trailing_zeros() as usize] = group_segments.trailing_zeros() as u8;
+        }
+
+        segments
+    }
+}
+
+// Chooses a glyph of the same size for every pattern, the output patterns first
+struct Search<'a, 'p> {
+    table: &'a GlyphTable,
+    output: &'p [Mask],
+    observed: &'p [Mask],
+}
+
+impl Search<'_, '_> {
+    fn pattern(&self, i: usize) -> Mask {
+        match i.checked_sub(self.output.len()) {
+            None => self.output[i],
+            Some(j) => self.observed[j],
+        }
+    }
+
+    fn glyphs(&self, pattern: Mask) -> impl Iterator<Item=&(char, Mask)> {
+        self.table.glyphs.iter().filter(move |(_, glyph)| glyph.count_ones() == pattern.count_ones())
+    }
+
+    // Hands every consistent choice for patterns i.. to visit, which returns false to end the search
+    fn complete(&self, i: usize, groups: Groups, visit: &mut impl FnMut(&Groups) -> bool) -> bool {
+        if i == self.output.len() + self.observed.len() {
+            return visit(&groups);
+        }
+
+        let pattern = self.pattern(i);
+
+        for (_, glyph) in self.glyphs(pattern) {
+            if let Some(groups) = groups.split(pattern, *glyph) {
+                if !self.complete(i + 1, groups, visit) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    // Returns false once max_readings are found
+    fn read(&self, i: usize, groups: Groups, reading: &mut String, readings: &mut Vec<String>, max_readings: usize) -> bool {
+        if i == self.output.len() {
+            if !readings.contains(reading) && !self.complete(i, groups, &mut |_| false) {
+                readings.push(reading.clone());
+            }
+
+            return readings.len() < max_readings;
+        }
+
+        let pattern = self.pattern(i);
+
+        for (symbol, glyph) in self.glyphs(pattern) {
+            if let Some(groups) = groups.split(pattern, *glyph) {
+                reading.push(*symbol);
+                let more = self.read(i + 1, groups, reading, readings, max_readings);
+                reading.pop();
+
+                if !more {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
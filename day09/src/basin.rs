@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use crate::map::Map;
+
+pub const DEFAULT_WALL_HEIGHT: u64 = 9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Basin {
+    pub low_point: (usize, usize),
+    // (row, column) of every cell, in the order the flood fill reached them
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Basin {
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+// Flood fills from each low point over the cells below the wall height. Low points that end up
+// in a basin found earlier (e.g. on a plateau) don't start a basin of their own.
+pub fn find_basins(map: &Map, low_points: &[(usize, usize)], wall_height: u64) -> Vec<Basin> {
+    let mut visited = vec![false; map.num_rows() * map.num_columns()];
+    let index = |(row, column): (usize, usize)| row * map.num_columns() + column;
+
+    let mut basins = vec![];
+
+    for &low_point in low_points {
+        if visited[index(low_point)] || map[low_point] >= wall_height {
+            continue;
+        }
+
+        let mut cells = vec![];
+        let mut queue = VecDeque::from([low_point]);
+        visited[index(low_point)] = true;
+
+        while let Some(cell) = queue.pop_front() {
+            cells.push(cell);
+
+            for neighbor in map.neighbors(cell) {
+                if !visited[index(neighbor)] && map[neighbor] < wall_height {
+                    visited[index(neighbor)] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        basins.push(Basin { low_point, cells });
+    }
+
+    basins
+}
+
+pub fn largest_basins_product(basins: &[Basin], count: usize) -> usize {
+    let mut sizes = basins.iter().map(Basin::size).collect::<Vec<_>>();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+
+    sizes.iter().take(count).product()
+}
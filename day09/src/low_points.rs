@@ -0,0 +1,121 @@
+use crate::map::{Center, Map};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    // every neighbour is higher, so flat regions never count
+    Strict,
+    // no neighbour is lower
+    NonStrict,
+}
+
+// Only matters for non-strict comparison, where neighbouring cells of equal height can both be low
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlateauPolicy {
+    EachCell,
+    // one low point per flat region without any lower neighbour, at its first cell in row-major order
+    Once,
+    Ignore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LowPointOptions {
+    pub connectivity: Connectivity,
+    pub comparison: Comparison,
+    pub plateaus: PlateauPolicy,
+}
+
+// The full 3x3 window, every cell of a plateau counted
+impl Default for LowPointOptions {
+    fn default() -> Self {
+        LowPointOptions {
+            connectivity: Connectivity::Eight,
+            comparison: Comparison::NonStrict,
+            plateaus: PlateauPolicy::EachCell,
+        }
+    }
+}
+
+const ORTHOGONAL: [usize; 4] = [1, 3, 5, 7];
+const ALL: [usize; 8] = [0, 1, 2, 3, 5, 6, 7, 8];
+
+pub fn find_low_points(map: &Map, options: &LowPointOptions) -> Vec<(usize, usize)> {
+    let window_indices: &[usize] = match options.connectivity {
+        Connectivity::Four => &ORTHOGONAL,
+        Connectivity::Eight => &ALL,
+    };
+
+    let mut low_points = vec![];
+    let mut seen_plateau = vec![false; map.num_rows() * map.num_columns()];
+
+    for (index, window) in map.window_iter().enumerate() {
+        let current = window.center().unwrap();
+        let neighbors = window_indices.iter().filter_map(|i| window[*i]);
+
+        let is_low = match options.comparison {
+            Comparison::Strict => neighbors.clone().all(|value| value > current),
+            Comparison::NonStrict => neighbors.clone().all(|value| value >= current),
+        };
+        let is_flat = neighbors.clone().any(|value| value == current);
+
+        if !is_low {
+            continue;
+        }
+
+        let position = map.position_for_index(index).unwrap();
+
+        match options.plateaus {
+            _ if !is_flat => low_points.push(position),
+            PlateauPolicy::EachCell => low_points.push(position),
+            PlateauPolicy::Ignore => {}
+            PlateauPolicy::Once if !seen_plateau[index] => {
+                let plateau = flat_region(map, position, options.connectivity, &mut seen_plateau);
+                let drains = plateau.iter()
+                    .any(|cell| neighbors_of(map, *cell, options.connectivity).iter().any(|neighbor| map[*neighbor] < current));
+
+                if !drains {
+                    low_points.push(position);
+                }
+            }
+            PlateauPolicy::Once => {}
+        }
+    }
+
+    low_points
+}
+
+fn neighbors_of(map: &Map, position: (usize, usize), connectivity: Connectivity) -> Vec<(usize, usize)> {
+    let mut neighbors = map.neighbors(position);
+
+    if connectivity == Connectivity::Eight {
+        neighbors.extend(map.diagonal_neighbors(position));
+    }
+
+    neighbors
+}
+
+// Marks the cells of the region as seen while collecting them
+pub fn flat_region(map: &Map, start: (usize, usize), connectivity: Connectivity, seen: &mut [bool]) -> Vec<(usize, usize)> {
+    let index = |(row, column): (usize, usize)| row * map.num_columns() + column;
+    let mut region = vec![start];
+    let mut i = 0;
+    seen[index(start)] = true;
+
+    while i < region.len() {
+        for neighbor in neighbors_of(map, region[i], connectivity) {
+            if map[neighbor] == map[start] && !seen[index(neighbor)] {
+                seen[index(neighbor)] = true;
+                region.push(neighbor);
+            }
+        }
+
+        i += 1;
+    }
+
+    region
+}
@@ -1,32 +1,89 @@
+mod basin;
+mod low_points;
 mod map;
+mod render;
+mod watershed;
 
+use basin::{find_basins, largest_basins_product, DEFAULT_WALL_HEIGHT};
+use low_points::{find_low_points, Comparison, Connectivity, LowPointOptions, PlateauPolicy};
 use map::Map;
-use map::Center;
+use watershed::Watershed;
 
 fn main() {
     let input = include_str!("../resources/input");
+    let args = std::env::args().collect::<Vec<_>>();
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1));
+
+    // --connectivity 4|8, --strict and --plateaus each|once|ignore change what counts as a low point
+    let mut options = LowPointOptions::default();
+
+    if let Some(connectivity) = option("--connectivity") {
+        options.connectivity = match connectivity.as_str() {
+            "4" => Connectivity::Four,
+            "8" => Connectivity::Eight,
+            _ => panic!("Invalid connectivity {} (expected: 4 or 8)", connectivity),
+        };
+    }
+
+    if args.iter().any(|arg| arg == "--strict") {
+        options.comparison = Comparison::Strict;
+    }
+
+    if let Some(plateaus) = option("--plateaus") {
+        options.plateaus = match plateaus.as_str() {
+            "each" => PlateauPolicy::EachCell,
+            "once" => PlateauPolicy::Once,
+            "ignore" => PlateauPolicy::Ignore,
+            _ => panic!("Invalid plateau policy {} (expected: each, once or ignore)", plateaus),
+        };
+    }
 
     let map = Map::new(input);
-    let result = calculate_risk_level(&map);
+    let low_points = find_low_points(&map, &options);
+    let result = calculate_risk_level(&map, &low_points);
     println!("[1/2] Result: {}", result);
-}
 
-fn calculate_risk_level(map: &Map) -> u64 {
-    let result: u64 = map.window_iter().filter(
-        |window| {
-            let current = window.center().unwrap();
-            let smaller = window.into_iter()
-                .find(
-                    |value| if let Some(v) = value {
-                        v < &current
-                    } else {
-                        false
-                    }
-                );
-
-            smaller.is_none()
+    // --wall <height> lowers or raises the height that separates basins
+    let wall_height = match option("--wall") {
+        Some(height) => height.parse().expect("Invalid wall height"),
+        None => DEFAULT_WALL_HEIGHT,
+    };
+
+    let basins = find_basins(&map, &low_points, wall_height);
+    let result = largest_basins_product(&basins, 3);
+    println!("[2/2] Result: {}", result);
+
+    // render ansi|text|pgm <path>|ppm <path> shows the heightmap with its low points or basins
+    if let Some(format) = option("render") {
+        let path = args.iter().skip_while(|arg| *arg != "render").nth(2);
+
+        match (format.as_str(), path) {
+            ("ansi", _) => print!("{}", render::to_ansi(&map, &low_points)),
+            ("text", _) => println!("{}", map),
+            ("pgm", Some(path)) => std::fs::write(path, render::to_pgm(&map)).expect("Failed to write image"),
+            ("ppm", Some(path)) => std::fs::write(path, render::to_ppm(&map, &basins)).expect("Failed to write image"),
+            _ => eprintln!("Usage: render ansi|text|pgm <path>|ppm <path>"),
         }
-    ).map(|window| window.center().unwrap() + 1).sum();
+    }
+
+    // watershed prints where rain on every cell ends up and the passes between the basins
+    if args.iter().any(|arg| arg == "watershed") {
+        let watershed = Watershed::new(&map);
+        let sizes = watershed.basin_sizes();
+
+        for (low_point, size) in watershed.low_points.iter().zip(&sizes) {
+            println!("Basin at {:?}: {} cells", low_point, size);
+        }
+
+        for saddle in watershed.drainage_graph(&map) {
+            let (a, b) = saddle.basins;
+            println!("{:?} <-> {:?}: saddle at height {}", watershed.low_points[a], watershed.low_points[b], saddle.height);
+        }
+    }
+}
+
+fn calculate_risk_level(map: &Map, low_points: &[(usize, usize)]) -> u64 {
+    let result: u64 = low_points.iter().map(|position| map[*position] + 1).sum();
 
     result
 }
@@ -46,10 +103,104 @@ mod test {
     #[test]
     fn test_part1() {
         let map = Map::new(TEST_DATA);
-        let result = calculate_risk_level(&map);
+        let result = calculate_risk_level(&map, &find_low_points(&map, &LowPointOptions::default()));
         assert_eq!(15, result);
     }
 
     #[test]
-    fn test_part2() {}
-}
\ No newline at end of file
+    fn test_part2() {
+        let map = Map::new(TEST_DATA);
+        let basins = find_basins(&map, &find_low_points(&map, &LowPointOptions::default()), DEFAULT_WALL_HEIGHT);
+
+        let sizes = basins.iter().map(|basin| (basin.low_point, basin.size())).collect::<Vec<_>>();
+        assert_eq!(vec![((0, 1), 3), ((0, 9), 9), ((2, 2), 14), ((4, 6), 9)], sizes);
+        assert_eq!(1134, largest_basins_product(&basins, 3));
+
+        let basins = find_basins(&map, &find_low_points(&map, &LowPointOptions::default()), 8);
+        assert_eq!(vec![3, 9, 7, 6], basins.iter().map(|basin| basin.size()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_watershed() {
+        let map = Map::new(TEST_DATA);
+        let watershed = Watershed::new(&map);
+        assert_eq!(vec![(0, 1), (0, 9), (2, 2), (4, 6)], watershed.low_points);
+        assert_eq!(vec![6, 15, 18, 11], watershed.basin_sizes());
+        assert!(watershed.drainage_graph(&map).iter().all(|saddle| saddle.height == 9));
+
+        // the plateau of 4s is split between its exits by distance, ties go to the first exit
+        let map = Map::new("
+            44444
+            41434
+            44444
+            00522
+        ");
+        let watershed = Watershed::new(&map);
+        let options = LowPointOptions { connectivity: Connectivity::Four, comparison: Comparison::NonStrict, plateaus: PlateauPolicy::Once };
+        assert_eq!(find_low_points(&map, &options), watershed.low_points);
+
+        let labels = (0..4)
+            .map(|row| (0..5).map(|column| watershed.basin_of((row, column)).to_string()).collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["00011", "00011", "22033", "22233"], labels);
+
+        let graph = watershed.drainage_graph(&map).iter().map(|saddle| (saddle.basins, saddle.height)).collect::<Vec<_>>();
+        assert_eq!(vec![((0, 1), 4), ((0, 2), 4), ((0, 3), 4), ((1, 3), 4), ((2, 3), 5)], graph);
+    }
+
+    #[test]
+    fn test_low_point_options() {
+        let map = Map::new("
+            22333
+            21133
+            33343
+            00541
+        ");
+        let options = |connectivity, comparison, plateaus| LowPointOptions { connectivity, comparison, plateaus };
+
+        let low_points = find_low_points(&map, &options(Connectivity::Four, Comparison::NonStrict, PlateauPolicy::EachCell));
+        assert_eq!(vec![(0, 0), (0, 3), (0, 4), (1, 1), (1, 2), (1, 4), (3, 0), (3, 1), (3, 4)], low_points);
+
+        let low_points = find_low_points(&map, &LowPointOptions::default());
+        assert_eq!(vec![(0, 4), (1, 1), (1, 2), (1, 4), (3, 0), (3, 1), (3, 4)], low_points);
+
+        for connectivity in [Connectivity::Four, Connectivity::Eight] {
+            let low_points = find_low_points(&map, &options(connectivity, Comparison::NonStrict, PlateauPolicy::Once));
+            assert_eq!(vec![(1, 1), (3, 0), (3, 4)], low_points);
+
+            let low_points = find_low_points(&map, &options(connectivity, Comparison::NonStrict, PlateauPolicy::Ignore));
+            assert_eq!(vec![(3, 4)], low_points);
+
+            let low_points = find_low_points(&map, &options(connectivity, Comparison::Strict, PlateauPolicy::EachCell));
+            assert_eq!(vec![(3, 4)], low_points);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let map = Map::new(TEST_DATA);
+        assert_eq!(TEST_DATA.split_whitespace().collect::<Vec<_>>().join("\n"), map.to_string());
+
+        let low_points = find_low_points(&map, &LowPointOptions::default());
+        let view = render::to_ansi(&map, &low_points);
+        assert_eq!(5, view.lines().count());
+        assert_eq!(low_points.len(), view.matches("\x1b[1;31;").count());
+
+        let image = render::to_pgm(&map);
+        assert!(image.starts_with(b"P5\n10 5\n255\n"));
+        assert_eq!([56, 28, 255], image[image.len() - 50..][..3], "heights 2, 1 and 9 in the first row");
+
+        let basins = find_basins(&map, &low_points, DEFAULT_WALL_HEIGHT);
+        let image = render::to_ppm(&map, &basins);
+        let pixels = image[image.len() - 150..].chunks(3).collect::<Vec<_>>();
+        assert_eq!(&[0, 0, 0], pixels[2]);
+
+        let colors = basins.iter()
+            .map(|basin| {
+                let (row, column) = basin.low_point;
+                pixels[row * 10 + column]
+            })
+            .collect::<Vec<_>>();
+        assert!((0..colors.len()).all(|i| colors[i] != [0, 0, 0] && !colors[..i].contains(&colors[i])));
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+use std::fmt::Formatter;
 use std::ops::Index;
 
 pub trait Center {
@@ -18,7 +20,7 @@ impl Map {
 
         let data =
             lines.into_iter()
-                .flat_map(|line| line.trim().chars().into_iter()
+                .flat_map(|line| line.trim().chars()
                     .map(|char| char.to_digit(10).unwrap() as u64)).collect::<Vec<_>>();
 
         assert_eq!(data.len(), num_rows * num_columns);
@@ -30,14 +32,50 @@ impl Map {
         }
     }
 
-    pub fn window_iter(&self) -> WindowIterator {
+    pub fn window_iter(&self) -> WindowIterator<'_> {
         WindowIterator {
             map: self,
             current_index: 0,
         }
     }
 
-    fn position_for_index(&self, index: usize) -> Option<(usize, usize)> {
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.num_columns
+    }
+
+    // Orthogonal neighbours inside the map
+    pub fn neighbors(&self, (row, column): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut neighbors = vec![];
+
+        if row > 0 {
+            neighbors.push((row - 1, column));
+        }
+        if column > 0 {
+            neighbors.push((row, column - 1));
+        }
+        if column + 1 < self.num_columns {
+            neighbors.push((row, column + 1));
+        }
+        if row + 1 < self.num_rows {
+            neighbors.push((row + 1, column));
+        }
+
+        neighbors
+    }
+
+    pub fn diagonal_neighbors(&self, (row, column): (usize, usize)) -> Vec<(usize, usize)> {
+        let rows = row.saturating_sub(1)..=(row + 1).min(self.num_rows - 1);
+
+        rows.flat_map(|r| [column.wrapping_sub(1), column + 1].map(|c| (r, c)))
+            .filter(|(r, c)| *r != row && *c < self.num_columns)
+            .collect()
+    }
+
+    pub fn position_for_index(&self, index: usize) -> Option<(usize, usize)> {
         if index > self.data.len() - 1 {
             return None;
         }
@@ -49,6 +87,20 @@ impl Map {
     }
 }
 
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = self.data
+            .chunks(self.num_columns)
+            .map(|row| row.iter().map(|height| height.to_string()).collect::<String>())
+            .collect::<Vec<String>>();
+
+        write!(
+            f, "{}",
+            lines.join("\n")
+        )
+    }
+}
+
 impl Index<(usize, usize)> for Map {
     type Output = u64;
 
@@ -0,0 +1,90 @@
+use crate::basin::Basin;
+use crate::map::Map;
+
+const RESET: &str = "\x1b[0m";
+
+// Heights as digits on a blue (low) to white (high) background, low points in bold red
+pub fn to_ansi(map: &Map, low_points: &[(usize, usize)]) -> String {
+    let max_height = max_height(map);
+    let mut is_low_point = vec![false; map.num_rows() * map.num_columns()];
+    let mut view = String::new();
+
+    for (row, column) in low_points {
+        is_low_point[row * map.num_columns() + column] = true;
+    }
+
+    for row in 0..map.num_rows() {
+        for column in 0..map.num_columns() {
+            let height = map[(row, column)];
+            let [r, g, b] = height_color(height, max_height);
+            let foreground = if is_low_point[row * map.num_columns() + column] { "1;31" } else { "30" };
+
+            view += &format!("\x1b[{};48;2;{};{};{}m{}{}", foreground, r, g, b, height, RESET);
+        }
+
+        view.push('\n');
+    }
+
+    view
+}
+
+// Binary greyscale image of the heights
+pub fn to_pgm(map: &Map) -> Vec<u8> {
+    let max_height = max_height(map);
+
+    let mut image = format!("P5\n{} {}\n255\n", map.num_columns(), map.num_rows()).into_bytes();
+    image.extend(cells(map).map(|cell| (map[cell] * 255 / max_height) as u8));
+    image
+}
+
+// Binary colour image with a distinct colour per basin, darker towards the rim. Cells outside
+// all basins stay black.
+pub fn to_ppm(map: &Map, basins: &[Basin]) -> Vec<u8> {
+    let mut colors = vec![[0u8; 3]; map.num_rows() * map.num_columns()];
+    let max_height = max_height(map);
+
+    for (i, basin) in basins.iter().enumerate() {
+        let color = basin_color(i);
+
+        for (row, column) in &basin.cells {
+            let shade = 1.0 - 0.6 * map[(*row, *column)] as f64 / max_height as f64;
+            colors[row * map.num_columns() + column] = color.map(|channel| (channel as f64 * shade).round() as u8);
+        }
+    }
+
+    let mut image = format!("P6\n{} {}\n255\n", map.num_columns(), map.num_rows()).into_bytes();
+    image.extend(colors.iter().flatten());
+    image
+}
+
+fn cells(map: &Map) -> impl Iterator<Item=(usize, usize)> + '_ {
+    (0..map.num_rows()).flat_map(move |row| (0..map.num_columns()).map(move |column| (row, column)))
+}
+
+fn max_height(map: &Map) -> u64 {
+    cells(map).map(|cell| map[cell]).max().unwrap_or(0).max(1)
+}
+
+fn height_color(height: u64, max_height: u64) -> [u8; 3] {
+    let t = height as f64 / max_height as f64;
+    let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    [channel(t), channel(0.3 + 0.7 * t), 255]
+}
+
+// Hues spaced by the golden angle, so neighbouring basin indices get clearly different colours
+fn basin_color(index: usize) -> [u8; 3] {
+    let hue = (index as f64 * 137.508) % 360.0 / 60.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+
+    let (r, g, b) = match hue as usize {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+
+    [r, g, b].map(|channel: f64| (channel * 255.0).round() as u8)
+}
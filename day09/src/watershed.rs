@@ -0,0 +1,134 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::low_points::{find_low_points, flat_region, Comparison, Connectivity, LowPointOptions, PlateauPolicy};
+use crate::map::Map;
+
+// A pass between two neighbouring basins: the lowest height water has to rise to before it spills over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Saddle {
+    pub basins: (usize, usize),
+    pub height: u64,
+}
+
+pub struct Watershed {
+    num_columns: usize,
+    // basin index of every cell, row by row
+    labels: Vec<usize>,
+    // first cell (row-major) of each basin's lowest region
+    pub low_points: Vec<(usize, usize)>,
+}
+
+impl Watershed {
+    // Every cell drains to its lowest orthogonal neighbour if that one is strictly lower, the first
+    // in row-major order on a tie. Flat regions drain towards their nearest edge cell that has a
+    // lower neighbour, flat regions without one are the bottoms of the basins: the low points with
+    // orthogonal neighbours and one low point per plateau.
+    pub fn new(map: &Map) -> Self {
+        let num_columns = map.num_columns();
+        let num_cells = map.num_rows() * num_columns;
+        let position = |index: usize| (index / num_columns, index % num_columns);
+        let index = |(row, column): (usize, usize)| row * num_columns + column;
+
+        let options = LowPointOptions {
+            connectivity: Connectivity::Four,
+            comparison: Comparison::NonStrict,
+            plateaus: PlateauPolicy::Once,
+        };
+        let low_points = find_low_points(map, &options);
+
+        let mut labels = vec![usize::MAX; num_cells];
+        let mut seen = vec![false; num_cells];
+
+        for (label, low_point) in low_points.iter().enumerate() {
+            for cell in flat_region(map, *low_point, Connectivity::Four, &mut seen) {
+                labels[index(cell)] = label;
+            }
+        }
+
+        let mut downstream: Vec<Option<usize>> = vec![None; num_cells];
+        let mut queue = VecDeque::new();
+
+        for cell in 0..num_cells {
+            let lowest = map.neighbors(position(cell)).into_iter()
+                .filter(|neighbor| map[*neighbor] < map[position(cell)])
+                .min_by_key(|neighbor| (map[*neighbor], index(*neighbor)));
+
+            if let Some(lowest) = lowest {
+                downstream[cell] = Some(index(lowest));
+                queue.push_back(cell);
+            }
+        }
+
+        // breadth first from the exits, so every flat cell flows along a shortest path
+        while let Some(cell) = queue.pop_front() {
+            for neighbor in map.neighbors(position(cell)) {
+                let neighbor = index(neighbor);
+
+                if downstream[neighbor].is_none() && map[position(neighbor)] == map[position(cell)] {
+                    downstream[neighbor] = Some(cell);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        for start in 0..num_cells {
+            let mut path = vec![];
+            let mut cell = start;
+
+            while labels[cell] == usize::MAX {
+                path.push(cell);
+                cell = downstream[cell].unwrap();
+            }
+
+            for visited in path {
+                labels[visited] = labels[cell];
+            }
+        }
+
+        Watershed {
+            num_columns,
+            labels,
+            low_points,
+        }
+    }
+
+    pub fn basin_of(&self, (row, column): (usize, usize)) -> usize {
+        self.labels[row * self.num_columns + column]
+    }
+
+    pub fn basin_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.low_points.len()];
+
+        for label in &self.labels {
+            sizes[*label] += 1;
+        }
+
+        sizes
+    }
+
+    // Neighbouring basins with the lowest pass between them, ordered by basin indices
+    pub fn drainage_graph(&self, map: &Map) -> Vec<Saddle> {
+        let mut saddles: HashMap<(usize, usize), u64> = HashMap::new();
+
+        for (i, label) in self.labels.iter().enumerate() {
+            let cell = (i / self.num_columns, i % self.num_columns);
+
+            for neighbor in map.neighbors(cell) {
+                let other = self.basin_of(neighbor);
+
+                if *label < other {
+                    let height = map[cell].max(map[neighbor]);
+                    let saddle = saddles.entry((*label, other)).or_insert(height);
+                    *saddle = (*saddle).min(height);
+                }
+            }
+        }
+
+        let mut graph = saddles.into_iter()
+            .map(|(basins, height)| Saddle { basins, height })
+            .collect::<Vec<_>>();
+        graph.sort_unstable_by_key(|saddle| saddle.basins);
+
+        graph
+    }
+}